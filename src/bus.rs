@@ -0,0 +1,306 @@
+use std::fmt;
+
+use crate::ld65::parseLinkerConfig;
+use crate::ld65::ConfigError;
+use crate::ld65::MemoryArea;
+use crate::savestate::SaveStateError;
+use crate::savestate::StateReader;
+use crate::savestate::StateWriter;
+
+// The linker config mapache64 games are built with
+const MAPACHE64_CFG: &str = include_str!("../benchmark1/template/backend/mapache64.cfg");
+
+/// Everything the CPU reads and writes goes through a Bus, one call per bus
+/// cycle, so devices can be mapped into the address space. This stands in
+/// for py65's memory observers.
+pub trait Bus {
+    fn read(&mut self, addr: u16) -> u8;
+    fn write(&mut self, addr: u16, value: u8);
+    /// read without the side effects a device may have on read, so dumps,
+    /// debuggers and renderers can look at memory without disturbing a
+    /// device
+    fn peek(&self, addr: u16) -> u8;
+    /// a fault recorded since the last call, which step() and tick() return
+    /// as StepError::BusFault
+    fn takeFault(&mut self) -> Option<BusFault> {
+        None
+    }
+    /// write memory and any device state into a save state. The default
+    /// saves nothing, so a Bus has to override this and loadState for
+    /// Mpu6502::saveState to capture it.
+    fn saveState(&self, _out: &mut StateWriter) {}
+    /// names the layout saveState writes. Save states carry it, so one
+    /// from a different kind of bus is refused before anything is loaded.
+    fn saveStateKind(&self) -> &'static str {
+        ""
+    }
+    /// restore what saveState wrote. input holds exactly that, so a bus
+    /// should refuse leftover bytes, and check it all before changing
+    /// anything.
+    fn loadState(&mut self, _input: &mut StateReader) -> Result<(), SaveStateError> {
+        Ok(())
+    }
+}
+
+/// An access a MemoryMap was set up to trap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BusFault {
+    UnmappedRead(u16),
+    UnmappedWrite(u16),
+    RomWrite(u16),
+}
+
+impl fmt::Display for BusFault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BusFault::UnmappedRead(addr) => write!(f, "read from unmapped {:#06x}", addr),
+            BusFault::UnmappedWrite(addr) => write!(f, "write to unmapped {:#06x}", addr),
+            BusFault::RomWrite(addr) => write!(f, "write to ROM at {:#06x}", addr),
+        }
+    }
+}
+
+/// 64K of plain RAM, the default bus. Reads and writes are just array
+/// accesses, so it runs as fast as the old memory array.
+pub struct FlatRam {
+    pub memory: [u8; 0x10000],
+}
+
+impl FlatRam {
+    pub fn new() -> Self {
+        FlatRam {
+            memory: [0; 0x10000],
+        }
+    }
+}
+
+impl Default for FlatRam {
+    fn default() -> Self {
+        FlatRam::new()
+    }
+}
+
+/// What a MemoryMap does with accesses to an address
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegionKind {
+    Ram,
+    /// reads like RAM, writes are dropped like on the real board
+    Rom,
+    /// nothing answers, reads see whatever was last on the data bus
+    Unmapped,
+}
+
+/// The MEMORY areas of the linker config mapache64 games are built with,
+/// the ones MemoryMap::mapache64() maps
+pub fn mapache64Areas() -> Vec<MemoryArea> {
+    parseLinkerConfig(MAPACHE64_CFG).expect("mapache64.cfg parses")
+}
+
+/// A 64K address space split into RAM, ROM and unmapped regions. memory
+/// holds the contents of every region, so a host can load ROM through it.
+pub struct MemoryMap {
+    pub memory: [u8; 0x10000],
+    kinds: [RegionKind; 0x10000],
+    // the last value driven on the data bus, what an unmapped read returns
+    openBus: u8,
+    pub trapUnmapped: bool,
+    pub trapRomWrites: bool,
+    fault: Option<BusFault>,
+}
+
+impl MemoryMap {
+    /// everything starts unmapped
+    pub fn new() -> Self {
+        MemoryMap {
+            memory: [0; 0x10000],
+            kinds: [RegionKind::Unmapped; 0x10000],
+            openBus: 0,
+            trapUnmapped: false,
+            trapRomWrites: false,
+            fault: None,
+        }
+    }
+
+    /// The mapache64 board, read from the linker config games are built
+    /// with. Zero page, stack, RAM, VRAM and IO are writable, the firmware
+    /// and the game ROM are not.
+    pub fn mapache64() -> Self {
+        MemoryMap::fromLinkerConfig(MAPACHE64_CFG).expect("mapache64.cfg parses")
+    }
+
+    /// A map built from the MEMORY areas of an ld65 config, see
+    /// parseLinkerConfig. Linker configs leave out the stack page, it is
+    /// mapped as RAM unless an area covers it.
+    pub fn fromLinkerConfig(text: &str) -> Result<Self, ConfigError> {
+        let mut map = MemoryMap::new();
+        map.map(0x0100, 0x100, RegionKind::Ram);
+        for area in parseLinkerConfig(text)? {
+            map.map(area.start, area.size, area.kind);
+        }
+        Ok(map)
+    }
+
+    /// Later calls override earlier ones where they overlap
+    pub fn map(&mut self, start: u16, size: u32, kind: RegionKind) {
+        let end = (start as usize + size as usize).min(0x10000);
+        self.kinds[start as usize..end].fill(kind);
+    }
+
+    /// the region addr is in
+    pub fn kindAt(&self, addr: u16) -> RegionKind {
+        self.kinds[addr as usize]
+    }
+
+    /// copy bytes in starting at start, whatever the regions are
+    pub fn load(&mut self, start: u16, bytes: &[u8]) {
+        let end = (start as usize + bytes.len()).min(0x10000);
+        self.memory[start as usize..end].copy_from_slice(&bytes[..end - start as usize]);
+    }
+}
+
+impl Default for MemoryMap {
+    fn default() -> Self {
+        MemoryMap::new()
+    }
+}
+
+impl Bus for MemoryMap {
+    fn read(&mut self, addr: u16) -> u8 {
+        if self.kinds[addr as usize] == RegionKind::Unmapped {
+            if self.trapUnmapped {
+                self.fault = Some(BusFault::UnmappedRead(addr));
+            }
+            return self.openBus;
+        }
+        self.openBus = self.memory[addr as usize];
+        self.openBus
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        self.openBus = value;
+        match self.kinds[addr as usize] {
+            RegionKind::Ram => self.memory[addr as usize] = value,
+            RegionKind::Rom => {
+                if self.trapRomWrites {
+                    self.fault = Some(BusFault::RomWrite(addr));
+                }
+            }
+            RegionKind::Unmapped => {
+                if self.trapUnmapped {
+                    self.fault = Some(BusFault::UnmappedWrite(addr));
+                }
+            }
+        }
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        if self.kinds[addr as usize] == RegionKind::Unmapped {
+            self.openBus
+        } else {
+            self.memory[addr as usize]
+        }
+    }
+
+    fn takeFault(&mut self) -> Option<BusFault> {
+        self.fault.take()
+    }
+
+    // memory, then one byte per address for its region, then the data bus
+    // and trap settings
+    fn saveStateKind(&self) -> &'static str {
+        "MemoryMap"
+    }
+
+    fn saveState(&self, out: &mut StateWriter) {
+        out.bytes(&self.memory);
+        for kind in self.kinds {
+            out.u8(kind as u8);
+        }
+        out.u8(self.openBus);
+        out.bool(self.trapUnmapped);
+        out.bool(self.trapRomWrites);
+        match self.fault {
+            None => out.u8(0),
+            Some(BusFault::UnmappedRead(addr)) => {
+                out.u8(1);
+                out.u16(addr);
+            }
+            Some(BusFault::UnmappedWrite(addr)) => {
+                out.u8(2);
+                out.u16(addr);
+            }
+            Some(BusFault::RomWrite(addr)) => {
+                out.u8(3);
+                out.u16(addr);
+            }
+        }
+    }
+
+    fn loadState(&mut self, input: &mut StateReader) -> Result<(), SaveStateError> {
+        // memory, regions, 3 flags and a fault with or without its address
+        if ![0x20004, 0x20006].contains(&input.remaining()) {
+            return Err(SaveStateError::new("not a MemoryMap state"));
+        }
+        let memory = input.bytes(0x10000)?;
+        let mut kinds = [RegionKind::Unmapped; 0x10000];
+        for (kind, &byte) in kinds.iter_mut().zip(input.bytes(0x10000)?) {
+            *kind = match byte {
+                0 => RegionKind::Ram,
+                1 => RegionKind::Rom,
+                2 => RegionKind::Unmapped,
+                _ => return Err(SaveStateError::new(format!("{} is not a region kind", byte))),
+            };
+        }
+        let openBus = input.u8()?;
+        let trapUnmapped = input.bool()?;
+        let trapRomWrites = input.bool()?;
+        let fault = match input.u8()? {
+            0 => None,
+            1 => Some(BusFault::UnmappedRead(input.u16()?)),
+            2 => Some(BusFault::UnmappedWrite(input.u16()?)),
+            3 => Some(BusFault::RomWrite(input.u16()?)),
+            tag => return Err(SaveStateError::new(format!("{} is not a bus fault", tag))),
+        };
+
+        self.memory.copy_from_slice(memory);
+        self.kinds = kinds;
+        self.openBus = openBus;
+        self.trapUnmapped = trapUnmapped;
+        self.trapRomWrites = trapRomWrites;
+        self.fault = fault;
+        Ok(())
+    }
+}
+
+impl Bus for FlatRam {
+    #[inline]
+    fn read(&mut self, addr: u16) -> u8 {
+        self.memory[addr as usize]
+    }
+
+    #[inline]
+    fn write(&mut self, addr: u16, value: u8) {
+        self.memory[addr as usize] = value;
+    }
+
+    #[inline]
+    fn peek(&self, addr: u16) -> u8 {
+        self.memory[addr as usize]
+    }
+
+    fn saveStateKind(&self) -> &'static str {
+        "FlatRam"
+    }
+
+    fn saveState(&self, out: &mut StateWriter) {
+        out.bytes(&self.memory);
+    }
+
+    fn loadState(&mut self, input: &mut StateReader) -> Result<(), SaveStateError> {
+        if input.remaining() != 0x10000 {
+            return Err(SaveStateError::new("not a FlatRam state"));
+        }
+        self.memory.copy_from_slice(input.bytes(0x10000)?);
+        Ok(())
+    }
+}
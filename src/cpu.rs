@@ -0,0 +1,2131 @@
+use std::fmt;
+use std::ops::BitAnd;
+use std::ops::BitAndAssign;
+use std::ops::BitOr;
+use std::ops::BitOrAssign;
+use std::ops::Not;
+
+use crate::bus::Bus;
+use crate::bus::BusFault;
+use crate::bus::FlatRam;
+use crate::decode::AddressingMode;
+use crate::decode::OpcodeInfo;
+use crate::decode::NMOS_DECODE;
+use crate::decode::ROCKWELL_DECODE;
+use crate::decode::SC02_DECODE;
+use crate::decode::WDC_DECODE;
+use crate::savestate::SaveStateError;
+use crate::savestate::StateReader;
+use crate::savestate::StateWriter;
+use crate::savestate::SAVE_STATE_MAGIC;
+use crate::savestate::SAVE_STATE_VERSION;
+
+mod instructions;
+
+use instructions::initialize65C02Instructions;
+use instructions::initialize65SC02Instructions;
+use instructions::initializeNmosInstructions;
+use instructions::initializeR65C02Instructions;
+
+/// The p (status) register <https://en.wikibooks.org/wiki/6502_Assembly#Registers>
+/// The flags combine with | and &, like the bits they stand for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Status(u8);
+
+impl Status {
+    pub const NEGATIVE: Status = Status(128);
+    pub const OVERFLOW: Status = Status(64);
+    pub const UNUSED: Status = Status(32);
+    pub const BREAK: Status = Status(16);
+    pub const DECIMAL: Status = Status(8);
+    pub const INTERRUPT: Status = Status(4);
+    pub const ZERO: Status = Status(2);
+    pub const CARRY: Status = Status(1);
+
+    /// the status byte as PHP pushes it
+    pub const fn fromBits(bits: u8) -> Status {
+        Status(bits)
+    }
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// true if every flag in flags is set
+    pub fn contains(self, flags: Status) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+
+    pub fn insert(&mut self, flags: Status) {
+        self.0 |= flags.0;
+    }
+
+    pub fn remove(&mut self, flags: Status) {
+        self.0 &= !flags.0;
+    }
+
+    /// insert or remove flags depending on value
+    pub fn set(&mut self, flags: Status, value: bool) {
+        if value {
+            self.insert(flags);
+        } else {
+            self.remove(flags);
+        }
+    }
+}
+
+impl BitOr for Status {
+    type Output = Status;
+    fn bitor(self, other: Status) -> Status {
+        Status(self.0 | other.0)
+    }
+}
+
+impl BitAnd for Status {
+    type Output = Status;
+    fn bitand(self, other: Status) -> Status {
+        Status(self.0 & other.0)
+    }
+}
+
+impl Not for Status {
+    type Output = Status;
+    fn not(self) -> Status {
+        Status(!self.0)
+    }
+}
+
+impl BitOrAssign for Status {
+    fn bitor_assign(&mut self, other: Status) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitAndAssign for Status {
+    fn bitand_assign(&mut self, other: Status) {
+        self.0 &= other.0;
+    }
+}
+
+// Stand-in for the unstable bits the NMOS ANE and LXA opcodes OR into the
+// accumulator. Real chips vary between 0x00, 0xee and 0xff, setMagic()
+// changes it to match the part being tested.
+const DEFAULT_MAGIC: u8 = 0xee;
+
+// pre set memory addresses for certain operations
+const RESET: u16 = 0xfffc;
+const NMI: u16 = 0xfffa;
+const IRQ: u16 = 0xfffe;
+
+/// The chips we can emulate. They share the documented NMOS instruction set
+/// but differ in the opcodes they add, in what the unused opcodes do, and in
+/// quirks like the NMOS JMP ($xxff) page wrap and the decimal mode flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CpuVariant {
+    Nmos6502,
+    Wdc65C02,
+    Rockwell65C02,
+    Cmos65SC02,
+}
+
+impl CpuVariant {
+    fn instructions<B: Bus>(self) -> Instructions<B> {
+        match self {
+            CpuVariant::Nmos6502 => Mpu6502::<B>::NMOS_INSTRUCTIONS,
+            CpuVariant::Wdc65C02 => Mpu6502::<B>::WDC_INSTRUCTIONS,
+            CpuVariant::Rockwell65C02 => Mpu6502::<B>::ROCKWELL_INSTRUCTIONS,
+            CpuVariant::Cmos65SC02 => Mpu6502::<B>::SC02_INSTRUCTIONS,
+        }
+    }
+
+    /// everything but the NMOS 6502
+    pub fn isCmos(self) -> bool {
+        self != CpuVariant::Nmos6502
+    }
+
+    /// What every opcode is on the variant, like py65's instruct,
+    /// cycletime and extracycles lists in one place
+    pub fn decodeTable(self) -> &'static [OpcodeInfo; 256] {
+        match self {
+            CpuVariant::Nmos6502 => &NMOS_DECODE,
+            CpuVariant::Wdc65C02 => &WDC_DECODE,
+            CpuVariant::Rockwell65C02 => &ROCKWELL_DECODE,
+            CpuVariant::Cmos65SC02 => &SC02_DECODE,
+        }
+    }
+
+    /// Byte length and cycle count of the NOP an opcode missing from the
+    /// variant's table runs as, from the decode table. None for the NMOS
+    /// JAM opcodes, the only ones any table lacks, which lock up the chip.
+    pub fn undefinedOpcodeNop(self, opcode: u8) -> Option<(i32, i32)> {
+        let info = self.decodeTable()[opcode as usize];
+        if info.mnemonic == "JAM" {
+            return None;
+        }
+        Some((info.length as i32, info.cycles as i32))
+    }
+}
+
+/// Whether the CPU is running instructions, or idling after WAI or STP.
+/// Waiting ends on an IRQ or NMI, Stopped only on reset(). The clock keeps
+/// counting cycles in both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunState {
+    Running,
+    Waiting,
+    Stopped,
+}
+
+/// What step() does when it fetches an opcode the variant's table doesn't have
+pub enum UndefinedOpcodePolicy<B: Bus = FlatRam> {
+    /// leave pc on the opcode and return StepError::UndefinedOpcode
+    Halt,
+    /// run it as the variant's NOP from CpuVariant::undefinedOpcodeNop, or
+    /// for a JAM lock up in RunState::Stopped with pc just past it
+    Nop,
+    /// call the function with pc just past the opcode, like an instruction.
+    /// It takes a cycle for the opcode fetch and one for every bus access
+    /// the callback makes, as tick() counts them.
+    Callback(fn(&mut Mpu6502<B>, u8) -> Result<(), StepError>),
+}
+
+// derive would want B: Copy, but only the fn pointer gets copied
+impl<B: Bus> Clone for UndefinedOpcodePolicy<B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<B: Bus> Copy for UndefinedOpcodePolicy<B> {}
+
+/// Snapshot of the registers for error reports
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Registers {
+    pub pc: u16,
+    pub acc: u8,
+    pub x: u8,
+    pub y: u8,
+    pub sp: u8,
+    pub p: Status,
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PC={:04x} A={:02x} X={:02x} Y={:02x} SP={:02x} P={:08b}",
+            self.pc, self.acc, self.x, self.y, self.sp, self.p.bits()
+        )
+    }
+}
+
+/// Why step() or tick() stopped short
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StepError {
+    /// the variant has no instruction for opcode, see UndefinedOpcodePolicy
+    UndefinedOpcode {
+        opcode: u8,
+        address: u16,
+        registers: Registers,
+    },
+    /// the bus trapped an access the instruction made, the instruction
+    /// itself still ran to the end
+    BusFault {
+        fault: BusFault,
+        registers: Registers,
+    },
+}
+
+impl fmt::Display for StepError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StepError::UndefinedOpcode {
+                opcode,
+                address,
+                registers,
+            } => write!(
+                f,
+                "undefined opcode {:#04x} at {:#06x} ({})",
+                opcode, address, registers
+            ),
+            StepError::BusFault { fault, registers } => {
+                write!(f, "{} ({})", fault, registers)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StepError {}
+
+// The handler of every opcode, None where the variant has none
+type Instructions<B> = [Option<fn(&mut Mpu6502<B>)>; 256];
+
+/// A 6502 or 65C02 wired to a Bus. Create one with new() or withBus(),
+/// load memory through busMut(), call reset() and then step() or tick().
+pub struct Mpu6502<B: Bus = FlatRam> {
+    pc: u16,
+    acc: u8,
+    p: Status,
+    // The sp is always added onto the spBase to determine
+    // where in the stack we are storing the next stack
+    // value, sp decreases each time
+    sp: u8,
+    x: u8,
+    y: u8,
+
+    bus: B,
+
+    spBase: u16,
+
+    excycles: i32,
+    addcycles: bool,
+    processorCycles: u64,
+    decodeTable: &'static [OpcodeInfo; 256],
+    runState: RunState,
+    // The interrupt inputs. IRQ is taken for as long as the line is held
+    // and I is clear. NMI is taken once each time the line is asserted.
+    irqLine: bool,
+    nmiLine: bool,
+    nmiPending: bool,
+    // I as the poll on the last cycle of the previous instruction saw it.
+    // CLI, SEI and PLP change I after that poll, so they take effect one
+    // instruction late.
+    irqMasked: bool,
+    variant: CpuVariant,
+    // constant used by the unstable ANE and LXA opcodes, see DEFAULT_MAGIC
+    magic: u8,
+    undefinedOpcodePolicy: UndefinedOpcodePolicy<B>,
+    instructions: Instructions<B>,
+    // the opcode the last step ran, None for an interrupt or idle cycle
+    lastOpcode: Option<u8>,
+
+    // Bus accesses made by the current instruction, one per cycle
+    busAccesses: usize,
+    // tick() state: the registers and cycle count the instruction started
+    // with, the values of the accesses it has made so far, and how many
+    // accesses the current pass may have
+    tickLimit: Option<usize>,
+    tickStart: Option<TickStart>,
+    busLog: Vec<u8>,
+}
+
+// What tick() needs to rerun the instruction it is partway through
+#[derive(Clone, Copy)]
+struct TickStart {
+    registers: Registers,
+    cycles: u64,
+    interrupt: Option<u16>,
+}
+
+impl Mpu6502 {
+    /// A CPU on 64K of FlatRam
+    pub fn new(variant: CpuVariant) -> Self {
+        Mpu6502::withBus(variant, FlatRam::new())
+    }
+}
+
+impl<B: Bus> Mpu6502<B> {
+    // Built at compile time, once for each Bus type
+    const NMOS_INSTRUCTIONS: Instructions<B> = initializeNmosInstructions();
+    const WDC_INSTRUCTIONS: Instructions<B> = initialize65C02Instructions();
+    const ROCKWELL_INSTRUCTIONS: Instructions<B> = initializeR65C02Instructions();
+    const SC02_INSTRUCTIONS: Instructions<B> = initialize65SC02Instructions();
+
+    /// A CPU on any Bus
+    pub fn withBus(variant: CpuVariant, bus: B) -> Self {
+        let instructions = variant.instructions();
+        let decodeTable = variant.decodeTable();
+        // the registers as the chip powers up, memory still has to be
+        // loaded and reset() run to fetch pc from the reset vector
+        Mpu6502 {
+            pc: 0,
+            sp: 0,
+            acc: 0,
+            p: Status::UNUSED | Status::BREAK,
+            x: 0,
+            y: 0,
+            spBase: 0x100,
+            excycles: 0,
+            addcycles: false,
+            processorCycles: 0,
+            decodeTable,
+            runState: RunState::Running,
+            irqLine: false,
+            nmiLine: false,
+            nmiPending: false,
+            irqMasked: false,
+            bus,
+            variant,
+            magic: DEFAULT_MAGIC,
+            undefinedOpcodePolicy: UndefinedOpcodePolicy::Halt,
+            instructions,
+            lastOpcode: None,
+            busAccesses: 0,
+            tickLimit: None,
+            tickStart: None,
+            busLog: Vec::new(),
+        }
+    }
+
+    /// Run one instruction, or take a pending interrupt
+    pub fn step(&mut self) -> Result<(), StepError> {
+        // finish an instruction tick() left partway through
+        while self.tickStart.is_some() {
+            self.tick()?;
+        }
+        let interrupt = self.pendingInterrupt();
+        self.execute(interrupt)?;
+        self.takeBusFault()
+    }
+
+    /// Advance one clock cycle, making the one bus access the chip makes on
+    /// it. Returns true when the cycle completed an instruction.
+    ///
+    /// The instruction is run from its first cycle on every tick. The
+    /// accesses it already made are answered from busLog instead of the
+    /// bus, the next one is made for real, and the ones after it are
+    /// dropped. Until the last cycle the registers are put back as they
+    /// were when the instruction started.
+    pub fn tick(&mut self) -> Result<bool, StepError> {
+        let start = match self.tickStart {
+            Some(start) => start,
+            None => {
+                let interrupt = self.pendingInterrupt();
+                if self.runState != RunState::Running {
+                    self.execute(None)?;
+                    self.takeBusFault()?;
+                    return Ok(true);
+                }
+                TickStart {
+                    registers: self.registers(),
+                    cycles: self.processorCycles,
+                    interrupt,
+                }
+            }
+        };
+        self.setRegisters(start.registers);
+        self.runState = RunState::Running;
+
+        self.tickLimit = Some(self.busLog.len() + 1);
+        let result = self.execute(start.interrupt);
+        self.tickLimit = None;
+
+        if let Err(error) = result {
+            self.tickStart = None;
+            self.busLog.clear();
+            self.processorCycles = start.cycles;
+            return Err(error);
+        }
+        if self.busAccesses > self.busLog.len() {
+            self.tickStart = Some(start);
+            self.setRegisters(start.registers);
+            self.runState = RunState::Running;
+            self.processorCycles = start.cycles + self.busLog.len() as u64;
+            self.takeBusFault()?;
+            return Ok(false);
+        }
+        self.tickStart = None;
+        self.busLog.clear();
+        self.processorCycles = start.cycles + self.busAccesses as u64;
+        self.takeBusFault()?;
+        Ok(true)
+    }
+
+    fn takeBusFault(&mut self) -> Result<(), StepError> {
+        match self.bus.takeFault() {
+            Some(fault) => Err(StepError::BusFault {
+                fault,
+                registers: self.registers(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Drive the IRQ input, true holds the line asserted
+    pub fn setIrq(&mut self, asserted: bool) {
+        self.irqLine = asserted;
+    }
+
+    /// Drive the NMI input. Only the change to asserted triggers an NMI.
+    pub fn setNmi(&mut self, asserted: bool) {
+        if asserted && !self.nmiLine {
+            self.nmiPending = true;
+        }
+        self.nmiLine = asserted;
+    }
+
+    /// whether the CPU is running, waiting after WAI or stopped by STP
+    pub fn runState(&self) -> RunState {
+        self.runState
+    }
+
+    // The vector of the interrupt to take before the next instruction, if
+    // any. Either line wakes the CPU from WAI, even if I masks the IRQ.
+    // A stopped CPU ignores both.
+    fn pendingInterrupt(&mut self) -> Option<u16> {
+        if self.runState == RunState::Stopped {
+            return None;
+        }
+        if self.irqLine || self.nmiPending {
+            self.runState = RunState::Running;
+        }
+        if self.nmiPending {
+            self.nmiPending = false;
+            Some(NMI)
+        } else if self.irqLine && !self.irqMasked {
+            Some(IRQ)
+        } else {
+            None
+        }
+    }
+
+    fn execute(&mut self, interrupt: Option<u16>) -> Result<(), StepError> {
+        self.lastOpcode = None;
+        if self.runState != RunState::Running {
+            self.processorCycles += 1;
+            return Ok(());
+        }
+
+        self.busAccesses = 0;
+        if let Some(vector) = interrupt {
+            self.serviceInterrupt(vector);
+            self.irqMasked = true;
+            self.processorCycles += self.busAccesses as u64;
+            return Ok(());
+        }
+        let instructCode = self.ByteAt(self.pc);
+        self.pc = self.pc.wrapping_add(1);
+        self.lastOpcode = Some(instructCode);
+        let info = &self.decodeTable[instructCode as usize];
+        let cycles = info.cycles;
+        self.excycles = 0;
+        self.addcycles = info.pagePenalty != 0;
+
+        let instruction = match self.instructions[instructCode as usize] {
+            Some(instruction) => instruction,
+            None => return self.undefinedOpcode(instructCode),
+        };
+
+        let masked = self.p.contains(Status::INTERRUPT);
+        instruction(self);
+        self.irqMasked = match instructCode {
+            // PLP, CLI and SEI
+            0x28 | 0x58 | 0x78 => masked,
+            _ => self.p.contains(Status::INTERRUPT),
+        };
+        self.processorCycles += (cycles as i32 + self.excycles) as u64;
+        Ok(())
+    }
+
+    // The interrupt sequence is BRK without the opcode. The fetched opcode
+    // and the byte after it are thrown away, pc and p are pushed with B
+    // clear, and pc is loaded from the vector. The CMOS parts also clear D.
+    fn serviceInterrupt(&mut self, vector: u16) {
+        self.DummyRead(self.pc);
+        self.DummyRead(self.pc);
+        self.stPushWord(self.pc);
+        self.stPush(((self.p & !Status::BREAK) | Status::UNUSED).bits());
+
+        self.p |= Status::INTERRUPT;
+        if self.variant.isCmos() {
+            self.p &= !Status::DECIMAL;
+        }
+        self.pc = self.WordAt(vector);
+    }
+
+    fn undefinedOpcode(&mut self, opcode: u8) -> Result<(), StepError> {
+        match self.undefinedOpcodePolicy {
+            UndefinedOpcodePolicy::Halt => {
+                self.pc = self.pc.wrapping_sub(1);
+                Err(StepError::UndefinedOpcode {
+                    opcode,
+                    address: self.pc,
+                    registers: self.registers(),
+                })
+            }
+            UndefinedOpcodePolicy::Nop => {
+                let Some((length, cycles)) = self.variant.undefinedOpcodeNop(opcode) else {
+                    // only reset() gets a jammed chip going again
+                    self.runState = RunState::Stopped;
+                    self.processorCycles += self.busAccesses as u64;
+                    return Ok(());
+                };
+                // fetch the operand bytes, then idle on the next byte
+                for _ in 1..length {
+                    self.DummyRead(self.pc);
+                    self.pc = self.pc.wrapping_add(1);
+                }
+                for _ in length..cycles {
+                    self.DummyRead(self.pc);
+                }
+                self.processorCycles += cycles as u64;
+                Ok(())
+            }
+            UndefinedOpcodePolicy::Callback(callback) => {
+                let result = callback(self, opcode);
+                // a cycle for each access, as tick() counts them
+                self.processorCycles += self.busAccesses as u64;
+                result
+            }
+        }
+    }
+
+    /// The register file, for hosts and debuggers. Setting registers
+    /// between instructions is safe, tick() puts back the ones an
+    /// unfinished instruction started with.
+    pub fn registers(&self) -> Registers {
+        Registers {
+            pc: self.pc,
+            acc: self.acc,
+            x: self.x,
+            y: self.y,
+            sp: self.sp,
+            p: self.p,
+        }
+    }
+
+    pub fn setRegisters(&mut self, registers: Registers) {
+        self.pc = registers.pc;
+        self.acc = registers.acc;
+        self.x = registers.x;
+        self.y = registers.y;
+        self.sp = registers.sp;
+        self.setStatus(registers.p);
+    }
+
+    /// The registers one at a time
+    pub fn pc(&self) -> u16 {
+        self.pc
+    }
+
+    pub fn setPc(&mut self, pc: u16) {
+        self.pc = pc;
+    }
+
+    pub fn acc(&self) -> u8 {
+        self.acc
+    }
+
+    pub fn setAcc(&mut self, acc: u8) {
+        self.acc = acc;
+    }
+
+    pub fn x(&self) -> u8 {
+        self.x
+    }
+
+    pub fn setX(&mut self, x: u8) {
+        self.x = x;
+    }
+
+    pub fn y(&self) -> u8 {
+        self.y
+    }
+
+    pub fn setY(&mut self, y: u8) {
+        self.y = y;
+    }
+
+    pub fn sp(&self) -> u8 {
+        self.sp
+    }
+
+    pub fn setSp(&mut self, sp: u8) {
+        self.sp = sp;
+    }
+
+    pub fn status(&self) -> Status {
+        self.p
+    }
+
+    /// I set here masks an IRQ from the next instruction on, unlike SEI
+    pub fn setStatus(&mut self, p: Status) {
+        self.p = p;
+        self.irqMasked = p.contains(Status::INTERRUPT);
+    }
+
+    /// clock cycles run since reset()
+    pub fn processorCycles(&self) -> u64 {
+        self.processorCycles
+    }
+
+    /// The opcode of the instruction the last step() ran, or the one tick()
+    /// is running. None when it took an interrupt or idled after WAI or STP.
+    pub fn lastOpcode(&self) -> Option<u8> {
+        self.lastOpcode
+    }
+
+    pub fn variant(&self) -> CpuVariant {
+        self.variant
+    }
+
+    pub fn setUndefinedOpcodePolicy(&mut self, policy: UndefinedOpcodePolicy<B>) {
+        self.undefinedOpcodePolicy = policy;
+    }
+
+    /// see DEFAULT_MAGIC
+    pub fn setMagic(&mut self, magic: u8) {
+        self.magic = magic;
+    }
+
+    /// The bus the CPU runs on, to load and inspect memory and devices
+    pub fn bus(&self) -> &B {
+        &self.bus
+    }
+
+    pub fn busMut(&mut self) -> &mut B {
+        &mut self.bus
+    }
+
+    // The address sp points at in the stack page
+    fn StackAddr(&self) -> u16 {
+        self.spBase + self.sp as u16
+    }
+
+    fn stPush(&mut self, z: u8) {
+        self.SetByteAt(self.StackAddr(), z);
+        self.sp = self.sp.wrapping_sub(1);
+    }
+
+    fn stPop(&mut self) -> u8 {
+        self.sp = self.sp.wrapping_add(1);
+        self.ByteAt(self.StackAddr())
+    }
+
+    fn stPushWord(&mut self, z: u16) {
+        self.stPush((z >> 8) as u8);
+        self.stPush(z as u8)
+    }
+
+    fn stPopWord(&mut self) -> u16 {
+        let lo = self.stPop() as u16;
+        lo | ((self.stPop() as u16) << 8)
+    }
+
+    // Every read and write goes through ByteAt and SetByteAt, one bus
+    // access per cycle. While tick() replays an instruction, this returns
+    // Some(value) for an access that must not reach the bus again.
+    fn BusReplay(&mut self) -> Option<u8> {
+        let index = self.busAccesses;
+        self.busAccesses += 1;
+        match self.tickLimit {
+            Some(_) if index < self.busLog.len() => Some(self.busLog[index]),
+            Some(limit) if index >= limit => Some(0),
+            _ => None,
+        }
+    }
+
+    fn ByteAt(&mut self, addr: u16) -> u8 {
+        if let Some(val) = self.BusReplay() {
+            return val;
+        }
+        let val = self.bus.read(addr);
+        if self.tickLimit.is_some() {
+            self.busLog.push(val);
+        }
+        val
+    }
+
+    fn SetByteAt(&mut self, addr: u16, value: u8) {
+        if self.BusReplay().is_some() {
+            return;
+        }
+        self.bus.write(addr, value);
+        if self.tickLimit.is_some() {
+            self.busLog.push(value);
+        }
+    }
+
+    // A read the chip makes while busy with something else. The value is
+    // thrown away, but the read still happens on the bus.
+    fn DummyRead(&mut self, addr: u16) {
+        self.ByteAt(addr);
+    }
+
+    // The cycle between the read and the write of a read-modify-write
+    // instruction. NMOS writes the unmodified value back, CMOS reads the
+    // address again.
+    fn ModifyCycle(&mut self, addr: u16, value: u8) {
+        if self.variant.isCmos() {
+            self.DummyRead(addr);
+        } else {
+            self.SetByteAt(addr, value);
+        }
+    }
+
+    fn ReadModifyWrite(&mut self, addr: u16, modify: fn(&mut Mpu6502<B>, u8) -> u8) -> u8 {
+        let tbyte = self.ByteAt(addr);
+        self.ModifyCycle(addr, tbyte);
+        let result = modify(self, tbyte);
+        self.SetByteAt(addr, result);
+        result
+    }
+
+    fn WordAt(&mut self, addr: u16) -> u16 {
+        let lo = self.ByteAt(addr) as u16;
+        lo | ((self.ByteAt(addr.wrapping_add(1)) as u16) << 8)
+    }
+
+    // WordAt, except the high byte comes from the start of the page when
+    // addr is the last byte of one
+    fn WrapAt(&mut self, addr: u16) -> u16 {
+        let wrapped_addr = (addr & 0xff00) | (addr as u8).wrapping_add(1) as u16;
+        let lo = self.ByteAt(addr) as u16;
+        lo | ((self.ByteAt(wrapped_addr) as u16) << 8)
+    }
+
+    fn ProgramCounter(&mut self) -> u16 {
+        self.pc
+    }
+
+    fn ImmediateByte(&mut self) -> u8 {
+        self.ByteAt(self.pc)
+    }
+
+    fn FlagsNZ(&mut self, value: u8) {
+        self.p.set(Status::ZERO, value == 0);
+        self.p.set(Status::NEGATIVE, value & 0x80 != 0);
+    }
+
+    /// The reset sequence runs like an interrupt whose pushes are turned
+    /// into reads, so sp still drops by three. It sets I, loads pc from the
+    /// reset vector and takes 7 cycles. A, X and Y are left alone.
+    pub fn reset(&mut self) {
+        self.processorCycles = 0;
+        self.runState = RunState::Running;
+        self.nmiPending = false;
+        self.tickStart = None;
+        self.busLog.clear();
+
+        self.busAccesses = 0;
+        self.DummyRead(self.pc);
+        self.DummyRead(self.pc);
+        for _ in 0..3 {
+            self.DummyRead(self.StackAddr());
+            self.sp = self.sp.wrapping_sub(1);
+        }
+        self.p |= Status::INTERRUPT | Status::BREAK | Status::UNUSED;
+        if self.variant.isCmos() {
+            self.p &= !Status::DECIMAL;
+        }
+        self.pc = self.WordAt(RESET);
+        self.irqMasked = true;
+        self.processorCycles += self.busAccesses as u64;
+    }
+
+    /// Start running at address instead of where the reset vector points,
+    /// like py65's goto
+    pub fn goto(&mut self, address: u16) {
+        self.pc = address;
+        self.runState = RunState::Running;
+        self.tickStart = None;
+        self.busLog.clear();
+    }
+
+    /// The whole machine as a save state: registers, cycle count,
+    /// interrupt lines, run state, an instruction tick() is partway through
+    /// and the bus through Bus::saveState. The undefined opcode policy is
+    /// host setup and isn't saved.
+    pub fn saveState(&self) -> Vec<u8> {
+        let mut out = StateWriter::new();
+        out.bytes(SAVE_STATE_MAGIC);
+        out.u16(SAVE_STATE_VERSION);
+        let kind = self.bus.saveStateKind();
+        out.u8(kind.len() as u8);
+        out.bytes(kind.as_bytes());
+        out.u8(match self.variant {
+            CpuVariant::Nmos6502 => 0,
+            CpuVariant::Wdc65C02 => 1,
+            CpuVariant::Rockwell65C02 => 2,
+            CpuVariant::Cmos65SC02 => 3,
+        });
+        writeRegisters(&mut out, self.registers());
+        out.u64(self.processorCycles);
+        out.u8(match self.runState {
+            RunState::Running => 0,
+            RunState::Waiting => 1,
+            RunState::Stopped => 2,
+        });
+        out.bool(self.irqLine);
+        out.bool(self.nmiLine);
+        out.bool(self.nmiPending);
+        out.bool(self.irqMasked);
+        out.u8(self.magic);
+        match self.lastOpcode {
+            Some(opcode) => {
+                out.bool(true);
+                out.u8(opcode);
+            }
+            None => out.bool(false),
+        }
+        match self.tickStart {
+            Some(start) => {
+                out.bool(true);
+                writeRegisters(&mut out, start.registers);
+                out.u64(start.cycles);
+                match start.interrupt {
+                    Some(vector) => {
+                        out.bool(true);
+                        out.u16(vector);
+                    }
+                    None => out.bool(false),
+                }
+                out.u32(self.busLog.len() as u32);
+                out.bytes(&self.busLog);
+            }
+            None => out.bool(false),
+        }
+
+        let mut bus = StateWriter::new();
+        self.bus.saveState(&mut bus);
+        let bus = bus.finish();
+        out.u32(bus.len() as u32);
+        out.bytes(&bus);
+        out.finish()
+    }
+
+    /// Restore a save state from saveState(), variant included. Nothing
+    /// changes if it is refused.
+    pub fn loadState(&mut self, state: &[u8]) -> Result<(), SaveStateError> {
+        let mut input = StateReader::new(state);
+        if input.bytes(SAVE_STATE_MAGIC.len()).ok() != Some(&SAVE_STATE_MAGIC[..]) {
+            return Err(SaveStateError::new("not a save state"));
+        }
+        let version = input.u16()?;
+        if version != SAVE_STATE_VERSION {
+            return Err(SaveStateError::new(format!(
+                "version {} isn't supported, only {}",
+                version, SAVE_STATE_VERSION
+            )));
+        }
+        let length = input.u8()? as usize;
+        let kind = input.bytes(length)?;
+        if kind != self.bus.saveStateKind().as_bytes() {
+            return Err(SaveStateError::new(format!(
+                "is a {} state, not a {} one",
+                String::from_utf8_lossy(kind),
+                self.bus.saveStateKind()
+            )));
+        }
+        let variant = match input.u8()? {
+            0 => CpuVariant::Nmos6502,
+            1 => CpuVariant::Wdc65C02,
+            2 => CpuVariant::Rockwell65C02,
+            3 => CpuVariant::Cmos65SC02,
+            value => return Err(SaveStateError::new(format!("{} is not a cpu variant", value))),
+        };
+        let registers = readRegisters(&mut input)?;
+        let processorCycles = input.u64()?;
+        let runState = match input.u8()? {
+            0 => RunState::Running,
+            1 => RunState::Waiting,
+            2 => RunState::Stopped,
+            value => return Err(SaveStateError::new(format!("{} is not a run state", value))),
+        };
+        let irqLine = input.bool()?;
+        let nmiLine = input.bool()?;
+        let nmiPending = input.bool()?;
+        let irqMasked = input.bool()?;
+        let magic = input.u8()?;
+        let lastOpcode = match input.bool()? {
+            true => Some(input.u8()?),
+            false => None,
+        };
+        let mut busLog = Vec::new();
+        let tickStart = match input.bool()? {
+            true => {
+                let registers = readRegisters(&mut input)?;
+                let cycles = input.u64()?;
+                let interrupt = match input.bool()? {
+                    true => Some(input.u16()?),
+                    false => None,
+                };
+                let length = input.u32()? as usize;
+                busLog.extend_from_slice(input.bytes(length)?);
+                Some(TickStart {
+                    registers,
+                    cycles,
+                    interrupt,
+                })
+            }
+            false => None,
+        };
+        let length = input.u32()? as usize;
+        let mut bus = StateReader::new(input.bytes(length)?);
+        if input.remaining() != 0 {
+            return Err(SaveStateError::new("has bytes after the bus state"));
+        }
+        self.bus.loadState(&mut bus)?;
+        if bus.remaining() != 0 {
+            return Err(SaveStateError::new(format!(
+                "bus state has {} bytes this bus doesn't use",
+                bus.remaining()
+            )));
+        }
+
+        self.variant = variant;
+        self.instructions = variant.instructions();
+        self.decodeTable = variant.decodeTable();
+        self.setRegisters(registers);
+        self.processorCycles = processorCycles;
+        self.runState = runState;
+        self.irqLine = irqLine;
+        self.nmiLine = nmiLine;
+        self.nmiPending = nmiPending;
+        self.irqMasked = irqMasked;
+        self.magic = magic;
+        self.lastOpcode = lastOpcode;
+        self.tickStart = tickStart;
+        self.busLog = busLog;
+        Ok(())
+    }
+
+    /// The instruction at address in assembler syntax, and its length
+    pub fn disassemble(&self, address: u16) -> (String, u16) {
+        let info = self.decodeTable[self.bus.peek(address) as usize];
+        let byte = self.bus.peek(address.wrapping_add(1));
+        let word = byte as u16 | ((self.bus.peek(address.wrapping_add(2)) as u16) << 8);
+        // branch targets are relative to the next instruction
+        let next = address.wrapping_add(info.length as u16);
+        let operand = match info.mode {
+            AddressingMode::Imp => String::new(),
+            AddressingMode::Acc => "A".to_string(),
+            AddressingMode::Imm => format!("#${:02x}", byte),
+            AddressingMode::Zpg => format!("${:02x}", byte),
+            AddressingMode::Zpx => format!("${:02x},X", byte),
+            AddressingMode::Zpy => format!("${:02x},Y", byte),
+            AddressingMode::Abs => format!("${:04x}", word),
+            AddressingMode::Abx => format!("${:04x},X", word),
+            AddressingMode::Aby => format!("${:04x},Y", word),
+            AddressingMode::Ind => format!("(${:04x})", word),
+            AddressingMode::Inx => format!("(${:02x},X)", byte),
+            AddressingMode::Iny => format!("(${:02x}),Y", byte),
+            AddressingMode::Zpi => format!("(${:02x})", byte),
+            AddressingMode::Iax => format!("(${:04x},X)", word),
+            AddressingMode::Rel => {
+                format!("${:04x}", next.wrapping_add(byte as i8 as u16))
+            }
+            AddressingMode::Zpr => {
+                let offset = (word >> 8) as u8 as i8;
+                format!("${:02x},${:04x}", byte, next.wrapping_add(offset as u16))
+            }
+        };
+        let text = if operand.is_empty() {
+            info.mnemonic.to_string()
+        } else {
+            format!("{} {}", info.mnemonic, operand)
+        };
+        (text, info.length as u16)
+    }
+
+    fn opSTZ(&mut self, x: u16) {
+        self.SetByteAt(x, 0x00)
+    }
+
+    fn opTSB(&mut self, x: u16) {
+        let m = self.ByteAt(x);
+        self.p.set(Status::ZERO, (m & self.acc) == 0);
+        self.ModifyCycle(x, m);
+        self.SetByteAt(x, m | self.acc);
+    }
+
+    fn opTRB(&mut self, x: u16) {
+        let m = self.ByteAt(x);
+        self.p.set(Status::ZERO, (m & self.acc) == 0);
+        self.ModifyCycle(x, m);
+        self.SetByteAt(x, m & !self.acc);
+    }
+
+    // RMB and SMB take the mask to AND or OR into the byte, like py65
+    fn opRMB(&mut self, x: u16, mask: u8) {
+        let m = self.ByteAt(x);
+        self.ModifyCycle(x, m);
+        self.SetByteAt(x, m & mask);
+    }
+
+    fn opSMB(&mut self, x: u16, mask: u8) {
+        let m = self.ByteAt(x);
+        self.ModifyCycle(x, m);
+        self.SetByteAt(x, m | mask);
+    }
+
+    // BBR and BBS test a bit of a zero page byte, then branch like the
+    // other relative instructions using the byte after the zero page address
+    fn opBBR(&mut self, mask: u8) {
+        let addr = self.ZeroPageAddr();
+        let tbyte = self.ByteAt(addr);
+        self.DummyRead(addr);
+        self.pc = self.pc.wrapping_add(1);
+        if (tbyte & mask) == 0 {
+            self.BranchRelAddr();
+        } else {
+            self.DummyRead(self.pc);
+            self.pc = self.pc.wrapping_add(1);
+        }
+    }
+
+    fn opBBS(&mut self, mask: u8) {
+        let addr = self.ZeroPageAddr();
+        let tbyte = self.ByteAt(addr);
+        self.DummyRead(addr);
+        self.pc = self.pc.wrapping_add(1);
+        if (tbyte & mask) != 0 {
+            self.BranchRelAddr();
+        } else {
+            self.DummyRead(self.pc);
+            self.pc = self.pc.wrapping_add(1);
+        }
+    }
+
+    // The shifts, INC and DEC work on the accumulator when given None. On
+    // memory they run through ReadModifyWrite with one of the functions
+    // below, which the illegal NMOS opcodes share.
+    fn opASL(&mut self, x: Option<u16>) {
+        match x {
+            Some(addr) => {
+                self.ReadModifyWrite(addr, Self::ShiftLeft);
+            }
+            None => {
+                self.DummyRead(self.pc);
+                self.acc = self.ShiftLeft(self.acc);
+            }
+        }
+    }
+
+    fn ShiftLeft(&mut self, tbyte: u8) -> u8 {
+        self.p.set(Status::CARRY, tbyte & 0x80 != 0);
+        let result = tbyte << 1;
+        self.FlagsNZ(result);
+        result
+    }
+
+    fn opROL(&mut self, x: Option<u16>) {
+        match x {
+            Some(addr) => {
+                self.ReadModifyWrite(addr, Self::RotateLeft);
+            }
+            None => {
+                self.DummyRead(self.pc);
+                self.acc = self.RotateLeft(self.acc);
+            }
+        }
+    }
+
+    fn RotateLeft(&mut self, tbyte: u8) -> u8 {
+        let carry = self.p.contains(Status::CARRY) as u8;
+        self.p.set(Status::CARRY, tbyte & 0x80 != 0);
+        let result = (tbyte << 1) | carry;
+        self.FlagsNZ(result);
+        result
+    }
+
+    fn opROR(&mut self, x: Option<u16>) {
+        match x {
+            Some(addr) => {
+                self.ReadModifyWrite(addr, Self::RotateRight);
+            }
+            None => {
+                self.DummyRead(self.pc);
+                self.acc = self.RotateRight(self.acc);
+            }
+        }
+    }
+
+    fn RotateRight(&mut self, tbyte: u8) -> u8 {
+        let carry = self.p.contains(Status::CARRY) as u8;
+        self.p.set(Status::CARRY, tbyte & 1 != 0);
+        let result = (tbyte >> 1) | (carry << 7);
+        self.FlagsNZ(result);
+        result
+    }
+
+    fn IndirectAbsXAddr(&mut self) -> u16 {
+        self.WordAt(self.pc).wrapping_add(self.x as u16)
+    }
+
+    // (zp) wraps within zero page like (zp),Y, so ($ff) takes its high
+    // byte from $00. py65 reads $0100 there.
+    fn ZeroPageIndirectAddr(&mut self) -> u16 {
+        let byte_at = self.ByteAt(self.pc);
+        self.WrapAt(byte_at as u16)
+    }
+    fn AbsoluteYAddr(&mut self) -> u16 {
+        let a1 = self.WordAt(self.pc);
+        self.IndexedAddr(a1, self.y, self.pc.wrapping_add(1))
+    }
+
+    fn BranchRelAddr(&mut self) {
+        self.excycles += 1;
+        let offset = self.ImmediateByte() as i8;
+        self.pc = self.pc.wrapping_add(1);
+        self.DummyRead(self.pc);
+
+        let addr = self.pc.wrapping_add(offset as u16);
+
+        if (self.pc & 0xff00) != (addr & 0xff00) {
+            self.excycles += 1;
+            // the chip reads from the old page before fixing the high byte
+            self.DummyRead((self.pc & 0xff00) | (addr & 0x00ff));
+        }
+
+        self.pc = addr;
+    }
+
+    //__________________________________________________________________________________operations
+
+    fn opORA(&mut self, x: u16) {
+        self.acc |= self.ByteAt(x);
+        self.FlagsNZ(self.acc);
+    }
+
+    fn opAND(&mut self, x: u16) {
+        self.acc &= self.ByteAt(x);
+        self.FlagsNZ(self.acc);
+    }
+
+    fn opEOR(&mut self, x: u16) {
+        self.acc ^= self.ByteAt(x);
+        self.FlagsNZ(self.acc);
+    }
+
+    fn opBCL(&mut self, flag: Status) {
+        if self.p.contains(flag) {
+            self.DummyRead(self.pc);
+            self.pc = self.pc.wrapping_add(1);
+        } else {
+            self.BranchRelAddr();
+        }
+    }
+
+    fn opBST(&mut self, flag: Status) {
+        if self.p.contains(flag) {
+            self.BranchRelAddr();
+        } else {
+            self.DummyRead(self.pc);
+            self.pc = self.pc.wrapping_add(1);
+        }
+    }
+
+    fn opCLR(&mut self, flag: Status) {
+        self.DummyRead(self.pc);
+        self.p &= !flag;
+    }
+
+    fn opSET(&mut self, flag: Status) {
+        self.DummyRead(self.pc);
+        self.p |= flag;
+    }
+
+    fn opSTA(&mut self, x: u16) {
+        self.SetByteAt(x, self.acc);
+    }
+
+    fn opSTY(&mut self, x: u16) {
+        self.SetByteAt(x, self.y);
+    }
+
+    fn opBIT(&mut self, x: u16) {
+        let tbyte = self.ByteAt(x);
+        self.p.set(Status::ZERO, (self.acc & tbyte) == 0);
+        self.p &= !(Status::NEGATIVE | Status::OVERFLOW);
+        self.p |= Status::fromBits(tbyte) & (Status::NEGATIVE | Status::OVERFLOW);
+    }
+
+    fn opCMPR(&mut self, addr: u16, register_value: u8) {
+        let tbyte = self.ByteAt(addr);
+        self.Compare(register_value, tbyte);
+    }
+
+    fn Compare(&mut self, register_value: u8, tbyte: u8) {
+        self.p.set(Status::CARRY, register_value >= tbyte);
+        self.FlagsNZ(register_value.wrapping_sub(tbyte));
+    }
+
+    fn opLSR(&mut self, x: Option<u16>) {
+        match x {
+            Some(addr) => {
+                self.ReadModifyWrite(addr, Self::ShiftRight);
+            }
+            None => {
+                self.DummyRead(self.pc);
+                self.acc = self.ShiftRight(self.acc);
+            }
+        }
+    }
+
+    fn ShiftRight(&mut self, tbyte: u8) -> u8 {
+        self.p.set(Status::CARRY, tbyte & 1 != 0);
+        let result = tbyte >> 1;
+        self.FlagsNZ(result);
+        result
+    }
+
+    fn ZeroPageAddr(&mut self) -> u16 {
+        self.ByteAt(self.pc) as u16
+    }
+    fn ZeroPageXAddr(&mut self) -> u16 {
+        let byte_at = self.ByteAt(self.pc);
+        self.ZeroPageIndexCycle(byte_at);
+        byte_at.wrapping_add(self.x) as u16
+    }
+
+    fn ZeroPageYAddr(&mut self) -> u16 {
+        let byte_at = self.ByteAt(self.pc);
+        self.ZeroPageIndexCycle(byte_at);
+        byte_at.wrapping_add(self.y) as u16
+    }
+
+    // The cycle spent adding the index to a zero page address. NMOS reads
+    // the unindexed address, CMOS reads the operand again.
+    fn ZeroPageIndexCycle(&mut self, byte_at: u8) {
+        if self.variant.isCmos() {
+            self.DummyRead(self.pc);
+        } else {
+            self.DummyRead(byte_at as u16);
+        }
+    }
+
+    fn IndirectXAddr(&mut self) -> u16 {
+        let byte_at = self.ByteAt(self.pc);
+        self.ZeroPageIndexCycle(byte_at);
+        self.WrapAt(byte_at.wrapping_add(self.x) as u16)
+    }
+
+    fn IndirectYAddr(&mut self) -> u16 {
+        let byte_at = self.ByteAt(self.pc);
+        let a1 = self.WrapAt(byte_at as u16);
+        self.IndexedAddr(a1, self.y, self.pc)
+    }
+
+    fn AbsoluteAddr(&mut self) -> u16 {
+        self.WordAt(self.pc)
+    }
+
+    fn AbsoluteXAddr(&mut self) -> u16 {
+        let a1 = self.WordAt(self.pc);
+        self.IndexedAddr(a1, self.x, self.pc.wrapping_add(1))
+    }
+
+    // Adds the index for the abx, aby and iny modes. Crossing a page costs
+    // a cycle where the chip reads before the high byte is fixed up, or the
+    // last operand byte again on CMOS. Instructions that don't take
+    // extracycles (the stores and read-modify-writes) always spend it.
+    fn IndexedAddr(&mut self, a1: u16, index: u8, operand: u16) -> u16 {
+        let a2 = a1.wrapping_add(index as u16);
+        let crossed = (a1 & 0xff00) != (a2 & 0xff00);
+        if crossed || !self.addcycles {
+            if self.addcycles {
+                self.excycles += 1;
+            }
+            if self.variant.isCmos() {
+                self.DummyRead(operand);
+            } else {
+                self.DummyRead((a1 & 0xff00) | (a2 & 0x00ff));
+            }
+        }
+        a2
+    }
+    // NEW OPS 11/30
+    //TEMP FLAGSNZ
+
+    fn opSTX(&mut self, y: u16) {
+        self.SetByteAt(y, self.x);
+    }
+
+    fn opLDA(&mut self, x: u16) {
+        self.acc = self.ByteAt(x);
+        self.FlagsNZ(self.acc);
+    }
+    fn opLDY(&mut self, x: u16) {
+        self.y = self.ByteAt(x);
+        self.FlagsNZ(self.y);
+    }
+    fn opLDX(&mut self, y: u16) {
+        self.x = self.ByteAt(y);
+        self.FlagsNZ(self.x);
+    }
+    fn opDECR(&mut self, x: Option<u16>) {
+        match x {
+            Some(addr) => {
+                self.ReadModifyWrite(addr, Self::Decrement);
+            }
+            None => {
+                self.DummyRead(self.pc);
+                self.acc = self.Decrement(self.acc);
+            }
+        }
+    }
+    fn Decrement(&mut self, tbyte: u8) -> u8 {
+        let result = tbyte.wrapping_sub(1);
+        self.FlagsNZ(result);
+        result
+    }
+    fn opINCR(&mut self, x: Option<u16>) {
+        match x {
+            Some(addr) => {
+                self.ReadModifyWrite(addr, Self::Increment);
+            }
+            None => {
+                self.DummyRead(self.pc);
+                self.acc = self.Increment(self.acc);
+            }
+        }
+    }
+    fn Increment(&mut self, tbyte: u8) -> u8 {
+        let result = tbyte.wrapping_add(1);
+        self.FlagsNZ(result);
+        result
+    }
+    fn opADC(&mut self, x: u16) {
+        let data = self.ByteAt(x);
+        self.AddWithCarry(data);
+        self.DecimalCycle(x);
+    }
+    fn opSBC(&mut self, x: u16) {
+        let data = self.ByteAt(x);
+        self.SubtractWithBorrow(data);
+        self.DecimalCycle(x);
+    }
+    // the CMOS parts set N and Z from the decimally adjusted result, which
+    // costs them a cycle reading the operand again
+    fn DecimalCycle(&mut self, x: u16) {
+        if self.p.contains(Status::DECIMAL) && self.variant.isCmos() {
+            self.DummyRead(x);
+            self.excycles += 1;
+        }
+    }
+    // The arithmetic is done on i32 copies of the operands so the carries
+    // out of the nibbles and the byte can be checked directly
+    fn AddWithCarry(&mut self, data: u8) {
+        let data = data as i32;
+        let acc = self.acc as i32;
+        let carry = self.p.contains(Status::CARRY) as i32;
+
+        if self.p.contains(Status::DECIMAL) {
+            let mut halfcarry = 0;
+            let mut decimalcarry = 0;
+            let mut adjust0 = 0;
+            let mut adjust1 = 0;
+            let mut nibble0 = (data & 0xf) + (acc & 0xf) + carry;
+            if nibble0 > 9 {
+                adjust0 = 6;
+                halfcarry = 1;
+            }
+            let mut nibble1 = ((data >> 4) & 0xf) + ((acc >> 4) & 0xf) + halfcarry;
+            if nibble1 > 9 {
+                adjust1 = 6;
+                decimalcarry = 1;
+            }
+            //the ALU outputs are not decimally adjusted
+            nibble0 &= 0xf;
+            nibble1 &= 0xf;
+            let aluresult = (nibble1 << 4) + nibble0;
+
+            // the final A contents will be decimally adjusted
+            nibble0 = (nibble0 + adjust0) & 0xf;
+            nibble1 = (nibble1 + adjust1) & 0xf;
+
+            self.FlagsNZ(aluresult as u8);
+            self.p.set(Status::CARRY, decimalcarry == 1);
+            self.p.set(Status::OVERFLOW, ((!(acc ^ data) & (acc ^ aluresult)) & 0x80) != 0);
+            self.acc = ((nibble1 << 4) + nibble0) as u8;
+            if self.variant.isCmos() {
+                self.FlagsNZ(self.acc);
+            }
+        } else {
+            let result = data + acc + carry;
+            self.p.set(Status::OVERFLOW, (!(acc ^ data) & (acc ^ result)) & 0x80 != 0);
+            self.p.set(Status::CARRY, result > 0xff);
+            self.acc = result as u8;
+            self.FlagsNZ(self.acc);
+        }
+    }
+    fn SubtractWithBorrow(&mut self, data: u8) {
+        let data = data as i32;
+        let acc = self.acc as i32;
+        let carry = self.p.contains(Status::CARRY) as i32;
+
+        if self.p.contains(Status::DECIMAL) {
+            let mut halfcarry = 1;
+            let mut adjust0 = 0;
+            let mut adjust1 = 0;
+
+            let nibble0 = (acc & 0xf) + (!data & 0xf) + carry;
+            if nibble0 <= 0xf {
+                halfcarry = 0;
+                adjust0 = 10;
+            }
+            let nibble1 = ((acc >> 4) & 0xf) + ((!data >> 4) & 0xf) + halfcarry;
+            if nibble1 <= 0xf {
+                adjust1 = 10 << 4;
+            }
+            let mut aluresult = acc + (!data & 0xff) + carry;
+            let decimalcarry = aluresult > 0xff;
+            aluresult &= 0xff;
+            let nibble0 = (aluresult + adjust0) & 0xf;
+            let nibble1 = ((aluresult + adjust1) >> 4) & 0xf;
+
+            self.FlagsNZ(aluresult as u8);
+            self.p.set(Status::CARRY, decimalcarry);
+            self.p.set(Status::OVERFLOW, ((acc ^ data) & (acc ^ aluresult)) & 0x80 != 0);
+            self.acc = ((nibble1 << 4) + nibble0) as u8;
+            if self.variant.isCmos() {
+                self.FlagsNZ(self.acc);
+            }
+        } else {
+            let result = acc + (!data & 0xff) + carry;
+            self.p.set(Status::OVERFLOW, ((acc ^ data) & (acc ^ result) & 0x80) != 0);
+            self.p.set(Status::CARRY, result > 0xff);
+            self.acc = result as u8;
+            self.FlagsNZ(self.acc);
+        }
+    }
+
+    //__________________________________________________________________________________illegal NMOS operations
+    // The undocumented NMOS opcodes are the side effect of two documented
+    // operations sharing a decode line, so most are built from the helpers above.
+    // See: https://www.masswerk.at/nowgobang/2021/6502-illegal-opcodes
+
+    fn opSLO(&mut self, x: u16) {
+        self.acc |= self.ReadModifyWrite(x, Self::ShiftLeft);
+        self.FlagsNZ(self.acc);
+    }
+
+    fn opRLA(&mut self, x: u16) {
+        self.acc &= self.ReadModifyWrite(x, Self::RotateLeft);
+        self.FlagsNZ(self.acc);
+    }
+
+    fn opSRE(&mut self, x: u16) {
+        self.acc ^= self.ReadModifyWrite(x, Self::ShiftRight);
+        self.FlagsNZ(self.acc);
+    }
+
+    fn opRRA(&mut self, x: u16) {
+        let tbyte = self.ReadModifyWrite(x, Self::RotateRight);
+        self.AddWithCarry(tbyte);
+    }
+
+    fn opSAX(&mut self, x: u16) {
+        self.SetByteAt(x, self.acc & self.x);
+    }
+
+    fn opLAX(&mut self, x: u16) {
+        self.opLDA(x);
+        self.x = self.acc;
+    }
+
+    fn opDCP(&mut self, x: u16) {
+        let tbyte = self.ReadModifyWrite(x, Self::Decrement);
+        self.Compare(self.acc, tbyte);
+    }
+
+    fn opISC(&mut self, x: u16) {
+        let tbyte = self.ReadModifyWrite(x, Self::Increment);
+        self.SubtractWithBorrow(tbyte);
+    }
+
+    fn opANC(&mut self, x: u16) {
+        self.opAND(x);
+        self.p.set(Status::CARRY, self.acc & 0x80 != 0);
+    }
+
+    fn opALR(&mut self, x: u16) {
+        self.opAND(x);
+        self.acc = self.ShiftRight(self.acc);
+    }
+
+    fn opARR(&mut self, x: u16) {
+        let tbyte = self.acc & self.ByteAt(x);
+        let carry = self.p.contains(Status::CARRY) as u8;
+        self.acc = (tbyte >> 1) | (carry << 7);
+        self.FlagsNZ(self.acc);
+        self.p &= !(Status::CARRY | Status::OVERFLOW);
+
+        if !self.p.contains(Status::DECIMAL) {
+            if (self.acc & 0x40) != 0 {
+                self.p |= Status::CARRY;
+            }
+            if ((self.acc >> 6) ^ (self.acc >> 5)) & 1 != 0 {
+                self.p |= Status::OVERFLOW;
+            }
+            return;
+        }
+
+        // in decimal mode the result is BCD fixed up nibble by nibble
+        if ((tbyte ^ self.acc) & 0x40) != 0 {
+            self.p |= Status::OVERFLOW;
+        }
+        let low = tbyte & 0xf;
+        let high = tbyte >> 4;
+        if low + (low & 1) > 5 {
+            self.acc = (self.acc & 0xf0) | (self.acc.wrapping_add(6) & 0xf);
+        }
+        if high + (high & 1) > 5 {
+            self.p |= Status::CARRY;
+            self.acc = self.acc.wrapping_add(0x60);
+        }
+    }
+
+    fn opSBX(&mut self, x: u16) {
+        let tbyte = self.ByteAt(x);
+        let (result, borrow) = (self.acc & self.x).overflowing_sub(tbyte);
+        self.p.set(Status::CARRY, !borrow);
+        self.x = result;
+        self.FlagsNZ(self.x);
+    }
+
+    fn opLAS(&mut self, x: u16) {
+        let tbyte = self.ByteAt(x) & self.sp;
+        self.acc = tbyte;
+        self.x = tbyte;
+        self.sp = tbyte;
+        self.FlagsNZ(tbyte);
+    }
+
+    // ANE and LXA OR the accumulator with a value that depends on the
+    // chip and its temperature, self.magic stands in for it.
+    fn opANE(&mut self, x: u16) {
+        self.acc = (self.acc | self.magic) & self.x & self.ByteAt(x);
+        self.FlagsNZ(self.acc);
+    }
+
+    fn opLXA(&mut self, x: u16) {
+        self.acc = (self.acc | self.magic) & self.ByteAt(x);
+        self.x = self.acc;
+        self.FlagsNZ(self.acc);
+    }
+
+    // SHA, SHX, SHY and TAS store a value ANDed with the high byte of the
+    // base address plus one. If indexing crosses a page, the stored value
+    // also replaces the high byte of the target address.
+    fn opSHx(&mut self, base: u16, index: u8, value: u8) {
+        let mut addr = base.wrapping_add(index as u16);
+        let tbyte = value & ((base >> 8) as u8).wrapping_add(1);
+        self.DummyRead((base & 0xff00) | (addr & 0x00ff));
+        if (base & 0xff00) != (addr & 0xff00) {
+            addr = ((tbyte as u16) << 8) | (addr & 0x00ff);
+        }
+        self.SetByteAt(addr, tbyte);
+    }
+}
+
+fn writeRegisters(out: &mut StateWriter, registers: Registers) {
+    out.u16(registers.pc);
+    out.u8(registers.acc);
+    out.u8(registers.x);
+    out.u8(registers.y);
+    out.u8(registers.sp);
+    out.u8(registers.p.bits());
+}
+
+fn readRegisters(input: &mut StateReader) -> Result<Registers, SaveStateError> {
+    Ok(Registers {
+        pc: input.u16()?,
+        acc: input.u8()?,
+        x: input.u8()?,
+        y: input.u8()?,
+        sp: input.u8()?,
+        p: Status::fromBits(input.u8()?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::MemoryMap;
+    use crate::mapache64::runFrame;
+    use crate::mapache64::syncVblank;
+    use crate::mapache64::Buttons;
+    use crate::mapache64::Controller;
+    use crate::mapache64::Mapache64Bus;
+
+    const CMOS_VARIANTS: [CpuVariant; 3] =
+        [CpuVariant::Wdc65C02, CpuVariant::Rockwell65C02, CpuVariant::Cmos65SC02];
+
+    // A CPU at 0x0200 with the bytes there
+    fn cpuWith(variant: CpuVariant, code: &[u8]) -> Mpu6502 {
+        let mut cpu = Mpu6502::new(variant);
+        cpu.busMut().memory[0x0200..0x0200 + code.len()].copy_from_slice(code);
+        cpu.goto(0x0200);
+        cpu
+    }
+
+    #[test]
+    fn cmosTablesDefineEveryOpcode() {
+        for variant in CMOS_VARIANTS {
+            let cpu = Mpu6502::new(variant);
+            for opcode in 0..256 {
+                assert!(cpu.instructions[opcode].is_some(), "{:?} {:02x}", variant, opcode);
+            }
+        }
+    }
+
+    #[test]
+    fn reservedCmosNops() {
+        let reserved = [0x02, 0x22, 0x42, 0x44, 0x54, 0x5c, 0x62, 0x82, 0xc2, 0xd4, 0xdc, 0xe2, 0xf4, 0xfc, 0x03, 0x0b, 0xfb];
+        for variant in CMOS_VARIANTS {
+            for opcode in reserved {
+                let mut cpu = cpuWith(variant, &[opcode, 0x34, 0x12]);
+                let before = cpu.registers();
+                cpu.step().unwrap();
+                let info = variant.decodeTable()[opcode as usize];
+                assert_eq!(cpu.pc(), 0x0200 + info.length as u16, "{:02x}", opcode);
+                assert_eq!(cpu.processorCycles(), info.cycles as u64, "{:02x}", opcode);
+                assert_eq!(Registers { pc: before.pc, ..cpu.registers() }, before);
+            }
+        }
+        // the 65SC02 lacks the Rockwell bit instructions
+        let mut cpu = cpuWith(CpuVariant::Cmos65SC02, &[0x07, 0x0f]);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!((cpu.pc(), cpu.processorCycles()), (0x0202, 2));
+    }
+
+    #[test]
+    fn undefinedOpcodeHalt() {
+        let mut cpu = cpuWith(CpuVariant::Nmos6502, &[0xa9, 0x42, 0x02]);
+        cpu.step().unwrap();
+        let registers = cpu.registers();
+        match cpu.step() {
+            Err(StepError::UndefinedOpcode { opcode, address, registers: snapshot }) => {
+                assert_eq!((opcode, address), (0x02, 0x0202));
+                assert_eq!(snapshot, registers);
+                assert_eq!(snapshot.acc, 0x42);
+            }
+            other => panic!("{:?}", other),
+        }
+        // pc stays on the opcode, so it halts again
+        assert_eq!(cpu.pc(), 0x0202);
+        assert!(cpu.step().is_err());
+    }
+
+    #[test]
+    fn undefinedOpcodeNopJams() {
+        let mut cpu = cpuWith(CpuVariant::Nmos6502, &[0x12, 0xea]);
+        cpu.setUndefinedOpcodePolicy(UndefinedOpcodePolicy::Nop);
+        cpu.step().unwrap();
+        assert_eq!(cpu.runState(), RunState::Stopped);
+        assert_eq!((cpu.pc(), cpu.processorCycles()), (0x0201, 1));
+        // the NOP after it never runs, the chip only idles
+        cpu.step().unwrap();
+        assert_eq!((cpu.pc(), cpu.processorCycles()), (0x0201, 2));
+        assert_eq!(CpuVariant::Nmos6502.undefinedOpcodeNop(0x12), None);
+        assert_eq!(CpuVariant::Wdc65C02.undefinedOpcodeNop(0x5c), Some((3, 8)));
+    }
+
+    // takes the next byte as an operand and loads it into A
+    fn loadOperand(cpu: &mut Mpu6502, _opcode: u8) -> Result<(), StepError> {
+        cpu.acc = cpu.ByteAt(cpu.pc);
+        cpu.pc = cpu.pc.wrapping_add(1);
+        Ok(())
+    }
+
+    #[test]
+    fn undefinedOpcodeCallback() {
+        let mut cpu = cpuWith(CpuVariant::Nmos6502, &[0x22, 0x99]);
+        cpu.setUndefinedOpcodePolicy(UndefinedOpcodePolicy::Callback(loadOperand));
+        cpu.step().unwrap();
+        // the opcode fetch and the callback's read
+        assert_eq!((cpu.pc(), cpu.acc(), cpu.processorCycles()), (0x0202, 0x99, 2));
+    }
+
+    // Cycles the first instruction of code takes with X and Y set to index
+    fn cycles(variant: CpuVariant, index: u8, code: &[u8]) -> u64 {
+        let mut cpu = cpuWith(variant, code);
+        cpu.setX(index);
+        cpu.setY(index);
+        // (zp) pointers at 0x10 and 0x20 to 0x12f0
+        cpu.busMut().memory[0x10..0x12].copy_from_slice(&[0xf0, 0x12]);
+        cpu.busMut().memory[0x20..0x22].copy_from_slice(&[0xf0, 0x12]);
+        cpu.step().unwrap();
+        cpu.processorCycles()
+    }
+
+    #[test]
+    fn addressingModeCycles() {
+        let wdc = CpuVariant::Wdc65C02;
+        assert_eq!(cycles(wdc, 0, &[0xa9, 0x01]), 2); // LDA #
+        assert_eq!(cycles(wdc, 0, &[0xa5, 0x10]), 3); // LDA zp
+        assert_eq!(cycles(wdc, 0, &[0xb5, 0x10]), 4); // LDA zp,X
+        assert_eq!(cycles(wdc, 0, &[0xad, 0x00, 0x12]), 4); // LDA abs
+        assert_eq!(cycles(wdc, 1, &[0xbd, 0x00, 0x12]), 4); // LDA abs,X
+        assert_eq!(cycles(wdc, 1, &[0xb9, 0x00, 0x12]), 4); // LDA abs,Y
+        assert_eq!(cycles(wdc, 0, &[0xa1, 0x10]), 6); // LDA (zp,X)
+        assert_eq!(cycles(wdc, 1, &[0xb1, 0x10]), 5); // LDA (zp),Y
+        assert_eq!(cycles(wdc, 0, &[0xb2, 0x10]), 5); // LDA (zp)
+        assert_eq!(cycles(wdc, 0, &[0x8d, 0x00, 0x12]), 4); // STA abs
+        assert_eq!(cycles(wdc, 0, &[0x9d, 0x00, 0x12]), 5); // STA abs,X
+        assert_eq!(cycles(wdc, 0, &[0x4c, 0x00, 0x12]), 3); // JMP abs
+        assert_eq!(cycles(wdc, 0, &[0x20, 0x00, 0x12]), 6); // JSR
+    }
+
+    #[test]
+    fn pageCrossPenalty() {
+        for variant in [CpuVariant::Nmos6502, CpuVariant::Wdc65C02] {
+            assert_eq!(cycles(variant, 0x10, &[0xbd, 0xff, 0x12]), 5); // LDA abs,X
+            assert_eq!(cycles(variant, 0x10, &[0xb9, 0xff, 0x12]), 5); // LDA abs,Y
+            assert_eq!(cycles(variant, 0x10, &[0xb1, 0x10]), 6); // LDA (zp),Y
+            // stores always take the extra cycle
+            assert_eq!(cycles(variant, 0x10, &[0x9d, 0xff, 0x12]), 5); // STA abs,X
+        }
+    }
+
+    #[test]
+    fn branchCycles() {
+        let wdc = CpuVariant::Wdc65C02;
+        // Z is clear after power up, so BEQ falls through and BNE branches
+        assert_eq!(cycles(wdc, 0, &[0xf0, 0x10]), 2);
+        assert_eq!(cycles(wdc, 0, &[0xd0, 0x10]), 3);
+        // from 0x0202 back past 0x0200
+        assert_eq!(cycles(wdc, 0, &[0xd0, 0xf0]), 4);
+        assert_eq!(cycles(wdc, 0, &[0x80, 0xf0]), 4); // BRA
+    }
+
+    #[test]
+    fn decimalCycles() {
+        // SED, then ADC #: the CMOS parts take a cycle to fix up the flags
+        let mut nmos = cpuWith(CpuVariant::Nmos6502, &[0xf8, 0x69, 0x01]);
+        let mut wdc = cpuWith(CpuVariant::Wdc65C02, &[0xf8, 0x69, 0x01]);
+        for cpu in [&mut nmos, &mut wdc] {
+            cpu.step().unwrap();
+            cpu.step().unwrap();
+        }
+        assert_eq!((nmos.processorCycles(), wdc.processorCycles()), (4, 5));
+    }
+
+    #[test]
+    fn readModifyWriteAbsXCycles() {
+        // ASL abs,X
+        assert_eq!(cycles(CpuVariant::Nmos6502, 1, &[0x1e, 0x00, 0x12]), 7);
+        assert_eq!(cycles(CpuVariant::Nmos6502, 1, &[0x1e, 0xff, 0x12]), 7);
+        assert_eq!(cycles(CpuVariant::Wdc65C02, 1, &[0x1e, 0x00, 0x12]), 6);
+        assert_eq!(cycles(CpuVariant::Wdc65C02, 1, &[0x1e, 0xff, 0x12]), 7);
+        // INC abs,X keeps 7 on both
+        assert_eq!(cycles(CpuVariant::Nmos6502, 1, &[0xfe, 0x00, 0x12]), 7);
+        assert_eq!(cycles(CpuVariant::Wdc65C02, 1, &[0xfe, 0x00, 0x12]), 7);
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Access {
+        Read(u16, u8),
+        Write(u16, u8),
+    }
+
+    // FlatRam that records every access the CPU makes
+    struct LoggingBus {
+        ram: FlatRam,
+        log: Vec<Access>,
+    }
+
+    impl Bus for LoggingBus {
+        fn read(&mut self, addr: u16) -> u8 {
+            let value = self.ram.read(addr);
+            self.log.push(Access::Read(addr, value));
+            value
+        }
+
+        fn write(&mut self, addr: u16, value: u8) {
+            self.log.push(Access::Write(addr, value));
+            self.ram.write(addr, value);
+        }
+
+        fn peek(&self, addr: u16) -> u8 {
+            self.ram.peek(addr)
+        }
+    }
+
+    fn loggingCpu(variant: CpuVariant, code: &[u8]) -> Mpu6502<LoggingBus> {
+        let mut ram = FlatRam::new();
+        ram.memory[0x0200..0x0200 + code.len()].copy_from_slice(code);
+        ram.memory[0x1234] = 0x41;
+        let mut cpu = Mpu6502::withBus(variant, LoggingBus { ram, log: Vec::new() });
+        cpu.goto(0x0200);
+        cpu
+    }
+
+    #[test]
+    fn readModifyWriteAccesses() {
+        use Access::*;
+        // INC $1234
+        let code = [0xee, 0x34, 0x12];
+        let fetch = [Read(0x0200, 0xee), Read(0x0201, 0x34), Read(0x0202, 0x12), Read(0x1234, 0x41)];
+
+        // the NMOS part writes the old value back before the new one
+        let mut cpu = loggingCpu(CpuVariant::Nmos6502, &code);
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus().log[..4], fetch);
+        assert_eq!(cpu.bus().log[4..], [Write(0x1234, 0x41), Write(0x1234, 0x42)]);
+
+        // the CMOS parts read it again instead
+        let mut cpu = loggingCpu(CpuVariant::Wdc65C02, &code);
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus().log[..4], fetch);
+        assert_eq!(cpu.bus().log[4..], [Read(0x1234, 0x41), Write(0x1234, 0x42)]);
+    }
+
+    #[test]
+    fn tickMatchesStep() {
+        // LDX #$ff, LDA $1235,X, INC $1234, JSR $0300, BRK, with RTS at 0x0300
+        let mut code = vec![0xa2, 0xff, 0xbd, 0x35, 0x12, 0xee, 0x34, 0x12, 0x20, 0x00, 0x03, 0x00];
+        code.resize(0x100, 0xea);
+        code.push(0x60);
+        for variant in [CpuVariant::Nmos6502, CpuVariant::Wdc65C02] {
+            let mut stepped = loggingCpu(variant, &code);
+            let mut ticked = loggingCpu(variant, &code);
+            for _ in 0..6 {
+                stepped.step().unwrap();
+                // one access on each tick
+                loop {
+                    let accesses = ticked.bus().log.len();
+                    let done = ticked.tick().unwrap();
+                    assert_eq!(ticked.bus().log.len(), accesses + 1);
+                    if done {
+                        break;
+                    }
+                }
+                assert_eq!(ticked.registers(), stepped.registers());
+                assert_eq!(ticked.processorCycles(), stepped.processorCycles());
+            }
+            assert_eq!(ticked.bus().log, stepped.bus().log);
+            assert_eq!(ticked.bus().log.len() as u64, ticked.processorCycles());
+        }
+    }
+
+    // A CPU with handlers at 0x0400 for IRQ and 0x0500 for NMI
+    fn interruptCpu(variant: CpuVariant, code: &[u8]) -> Mpu6502 {
+        let mut cpu = cpuWith(variant, code);
+        cpu.busMut().memory[0xfffa..].copy_from_slice(&[0x00, 0x05, 0x00, 0x00, 0x00, 0x04]);
+        cpu.setSp(0xff);
+        cpu
+    }
+
+    #[test]
+    fn cliTakesEffectAfterTheNextInstruction() {
+        // CLI, NOP, NOP
+        let mut cpu = interruptCpu(CpuVariant::Wdc65C02, &[0x58, 0xea, 0xea]);
+        cpu.setStatus(Status::UNUSED | Status::INTERRUPT);
+        cpu.setIrq(true);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.pc(), 0x0202);
+        let cycles = cpu.processorCycles();
+        cpu.step().unwrap();
+        assert_eq!(cpu.pc(), 0x0400);
+        assert_eq!(cpu.processorCycles() - cycles, 7);
+        assert_eq!(cpu.bus().memory[0x1fe..0x200], [0x02, 0x02]);
+        // pushed with B clear, the I it had, and I set now
+        assert_eq!(cpu.bus().memory[0x1fd], Status::UNUSED.bits());
+        assert!(cpu.status().contains(Status::INTERRUPT));
+        // and the handler's first instruction runs before another poll
+        cpu.busMut().memory[0x0400] = 0xea;
+        cpu.step().unwrap();
+        assert_eq!(cpu.pc(), 0x0401);
+    }
+
+    #[test]
+    fn seiAndPlpTakeEffectLate() {
+        // SEI, NOP
+        let mut cpu = interruptCpu(CpuVariant::Wdc65C02, &[0x78, 0xea]);
+        cpu.step().unwrap();
+        cpu.setIrq(true);
+        cpu.step().unwrap();
+        assert_eq!(cpu.pc(), 0x0400);
+        // the pushed P already has I set
+        assert_eq!(cpu.bus().memory[0x1fd], (Status::UNUSED | Status::INTERRUPT).bits());
+
+        // LDA of I set, PHA, PLP, NOP
+        let mut cpu = interruptCpu(CpuVariant::Nmos6502, &[0xa9, 0x04, 0x48, 0x28, 0xea]);
+        for _ in 0..3 {
+            cpu.step().unwrap();
+        }
+        cpu.setIrq(true);
+        cpu.step().unwrap();
+        assert_eq!(cpu.pc(), 0x0400);
+    }
+
+    #[test]
+    fn nmiIsEdgeTriggered() {
+        let mut cpu = interruptCpu(CpuVariant::Nmos6502, &[0xea, 0xea]);
+        cpu.busMut().memory[0x0500..0x0503].fill(0xea);
+        cpu.setStatus(Status::UNUSED | Status::INTERRUPT);
+        cpu.setNmi(true);
+        cpu.step().unwrap();
+        assert_eq!((cpu.pc(), cpu.processorCycles()), (0x0500, 7));
+        // held, the line doesn't trigger again
+        cpu.step().unwrap();
+        assert_eq!(cpu.pc(), 0x0501);
+        cpu.setNmi(false);
+        cpu.step().unwrap();
+        cpu.setNmi(true);
+        cpu.step().unwrap();
+        assert_eq!(cpu.pc(), 0x0500);
+    }
+
+    #[test]
+    fn interruptsClearDecimalOnCmosOnly() {
+        for (variant, decimal) in [(CpuVariant::Nmos6502, true), (CpuVariant::Wdc65C02, false)] {
+            let mut cpu = interruptCpu(variant, &[0xf8]);
+            cpu.step().unwrap();
+            cpu.setIrq(true);
+            cpu.step().unwrap();
+            assert_eq!(cpu.pc(), 0x0400);
+            assert_eq!(cpu.status().contains(Status::DECIMAL), decimal);
+            assert_eq!(cpu.bus().memory[0x1fd], (Status::UNUSED | Status::DECIMAL).bits());
+        }
+    }
+
+    // Runs one NMOS instruction with the registers and carry given and 0x10
+    // holding operand, which is also a (zp) pointer to 0x1200 when 0x00
+    fn nmos(code: &[u8], acc: u8, x: u8, y: u8, carry: bool, operand: u8) -> Mpu6502 {
+        let mut cpu = cpuWith(CpuVariant::Nmos6502, code);
+        cpu.setRegisters(Registers {
+            pc: 0x0200,
+            acc,
+            x,
+            y,
+            sp: 0xff,
+            p: if carry { Status::UNUSED | Status::CARRY } else { Status::UNUSED },
+        });
+        cpu.busMut().memory[0x10..0x12].copy_from_slice(&[operand, 0x12]);
+        cpu.step().unwrap();
+        cpu
+    }
+
+    fn flags(cpu: &Mpu6502) -> (bool, bool, bool) {
+        let p = cpu.status();
+        (p.contains(Status::NEGATIVE), p.contains(Status::ZERO), p.contains(Status::CARRY))
+    }
+
+    #[test]
+    fn illegalLoadsAndStores() {
+        // LAX zp
+        let cpu = nmos(&[0xa7, 0x10], 0, 0, 0, false, 0x80);
+        assert_eq!((cpu.acc(), cpu.x(), flags(&cpu)), (0x80, 0x80, (true, false, false)));
+        // SAX zp stores A & X and leaves the flags
+        let cpu = nmos(&[0x87, 0x10], 0xf0, 0x3c, 0, false, 0);
+        assert_eq!((cpu.bus().memory[0x10], flags(&cpu)), (0x30, (false, false, false)));
+    }
+
+    #[test]
+    fn illegalReadModifyWrites() {
+        // DCP zp: decrement, then compare with A
+        let cpu = nmos(&[0xc7, 0x10], 0x42, 0, 0, false, 0x43);
+        assert_eq!((cpu.bus().memory[0x10], flags(&cpu)), (0x42, (false, true, true)));
+        // ISC zp: increment, then subtract with borrow
+        let cpu = nmos(&[0xe7, 0x10], 0x20, 0, 0, true, 0x0f);
+        assert_eq!((cpu.bus().memory[0x10], cpu.acc(), flags(&cpu)), (0x10, 0x10, (false, false, true)));
+        // SLO zp: shift left, then OR into A
+        let cpu = nmos(&[0x07, 0x10], 0x01, 0, 0, false, 0x81);
+        assert_eq!((cpu.bus().memory[0x10], cpu.acc(), flags(&cpu)), (0x02, 0x03, (false, false, true)));
+        // RLA zp: rotate left, then AND into A
+        let cpu = nmos(&[0x27, 0x10], 0xff, 0, 0, true, 0x81);
+        assert_eq!((cpu.bus().memory[0x10], cpu.acc(), flags(&cpu)), (0x03, 0x03, (false, false, true)));
+        // SRE zp: shift right, then EOR into A
+        let cpu = nmos(&[0x47, 0x10], 0x01, 0, 0, false, 0x03);
+        assert_eq!((cpu.bus().memory[0x10], cpu.acc(), flags(&cpu)), (0x01, 0x00, (false, true, true)));
+        // RRA zp: rotate right, then add with the carry it shifted out
+        let cpu = nmos(&[0x67, 0x10], 0x10, 0, 0, false, 0x03);
+        assert_eq!((cpu.bus().memory[0x10], cpu.acc(), flags(&cpu)), (0x01, 0x12, (false, false, false)));
+    }
+
+    #[test]
+    fn unstableImmediates() {
+        // ANE #$ff: (A | magic) & X & operand
+        let cpu = nmos(&[0x8b, 0xff], 0x01, 0x0f, 0, false, 0);
+        assert_eq!(cpu.acc(), 0x0f);
+        // LXA #$3c: (A | magic) & operand into A and X
+        let cpu = nmos(&[0xab, 0x3c], 0x00, 0, 0, false, 0);
+        assert_eq!((cpu.acc(), cpu.x()), (0x2c, 0x2c));
+        let mut cpu = cpuWith(CpuVariant::Nmos6502, &[0xab, 0x3c]);
+        cpu.setMagic(0xff);
+        cpu.step().unwrap();
+        assert_eq!((cpu.acc(), cpu.x()), (0x3c, 0x3c));
+    }
+
+    #[test]
+    fn unstableStores() {
+        // the value is ANDed with the base address's high byte plus one
+        let cpu = nmos(&[0x9e, 0x00, 0x12], 0, 0xff, 0x10, false, 0); // SHX abs,Y
+        assert_eq!(cpu.bus().memory[0x1210], 0x13);
+        let cpu = nmos(&[0x9c, 0x00, 0x12], 0, 0x10, 0xff, false, 0); // SHY abs,X
+        assert_eq!(cpu.bus().memory[0x1210], 0x13);
+        let cpu = nmos(&[0x9f, 0x00, 0x12], 0xff, 0x0f, 0x10, false, 0); // SHA abs,Y
+        assert_eq!(cpu.bus().memory[0x1210], 0x03);
+        let cpu = nmos(&[0x93, 0x10], 0xff, 0x0f, 0x10, false, 0); // SHA (zp),Y
+        assert_eq!(cpu.bus().memory[0x1210], 0x03);
+        // TAS abs,Y also puts A & X in sp
+        let cpu = nmos(&[0x9b, 0x00, 0x12], 0xff, 0xf3, 0x10, false, 0);
+        assert_eq!((cpu.sp(), cpu.bus().memory[0x1210]), (0xf3, 0x13));
+
+        // crossing into 0x1310, the stored 0x01 replaces the high byte
+        let cpu = nmos(&[0x9e, 0xf0, 0x12], 0, 0x05, 0x20, false, 0); // SHX abs,Y
+        assert_eq!((cpu.bus().memory[0x0110], cpu.bus().memory[0x1310]), (0x01, 0x00));
+        let cpu = nmos(&[0x9c, 0xf0, 0x12], 0, 0x20, 0x05, false, 0); // SHY abs,X
+        assert_eq!((cpu.bus().memory[0x0110], cpu.bus().memory[0x1310]), (0x01, 0x00));
+    }
+
+    // INC $10 and a controller read in a loop, with an IRQ handler that
+    // acknowledges the vblank and counts in $12
+    fn mapache64Cpu() -> Mpu6502<Mapache64Bus> {
+        let mut map = MemoryMap::mapache64();
+        map.load(0x8000, &[0x58, 0xe6, 0x10, 0xad, 0x02, 0x70, 0x85, 0x11, 0x4c, 0x01, 0x80]);
+        map.load(0x8010, &[0x8d, 0x01, 0x70, 0xe6, 0x12, 0x40]);
+        map.load(0xfffa, &[0x10, 0x80, 0x00, 0x80, 0x10, 0x80]);
+        // a frame every 1000 cycles
+        let mut cpu = Mpu6502::withBus(CpuVariant::Wdc65C02, Mapache64Bus::new(map, 60_000));
+        cpu.reset();
+        cpu
+    }
+
+    #[test]
+    fn saveStateRoundTrip() {
+        let mut cpu = mapache64Cpu();
+        cpu.busMut().setController(Controller::One, Buttons::A | Buttons::START);
+        runFrame(&mut cpu).unwrap();
+        runFrame(&mut cpu).unwrap();
+        for _ in 0..200 {
+            cpu.step().unwrap();
+            syncVblank(&mut cpu);
+        }
+        cpu.setNmi(true);
+        // partway through an instruction
+        cpu.tick().unwrap();
+        let state = cpu.saveState();
+
+        let mut loaded = Mpu6502::withBus(CpuVariant::Nmos6502, Mapache64Bus::new(MemoryMap::mapache64(), 60_000));
+        loaded.loadState(&state).unwrap();
+        assert_eq!(loaded.variant, CpuVariant::Wdc65C02);
+        assert_eq!(loaded.registers(), cpu.registers());
+        assert_eq!(loaded.processorCycles(), cpu.processorCycles());
+        assert_eq!(loaded.runState(), cpu.runState());
+        assert_eq!(
+            (loaded.irqLine, loaded.nmiLine, loaded.nmiPending, loaded.irqMasked),
+            (cpu.irqLine, cpu.nmiLine, cpu.nmiPending, cpu.irqMasked)
+        );
+        assert_eq!(loaded.bus().map.memory, cpu.bus().map.memory);
+        assert!(loaded.bus().map.memory[0x12] > 0);
+        assert_eq!(
+            (loaded.bus().inVblank(), loaded.bus().irqPending(), loaded.bus().frames(), loaded.bus().controller(Controller::One)),
+            (cpu.bus().inVblank(), cpu.bus().irqPending(), cpu.bus().frames(), cpu.bus().controller(Controller::One))
+        );
+        assert_eq!(loaded.saveState(), state);
+
+        // and both run on the same
+        for _ in 0..2 {
+            runFrame(&mut cpu).unwrap();
+            runFrame(&mut loaded).unwrap();
+        }
+        assert_eq!(loaded.saveState(), cpu.saveState());
+    }
+
+    #[test]
+    fn saveStateFromAnotherBus() {
+        let mut cpu = mapache64Cpu();
+        cpu.step().unwrap();
+        let registers = cpu.registers();
+        let error = cpu.loadState(&Mpu6502::new(CpuVariant::Nmos6502).saveState()).unwrap_err();
+        assert_eq!(error.message, "is a FlatRam state, not a Mapache64Bus one");
+        assert_eq!((cpu.registers(), cpu.processorCycles()), (registers, 9));
+    }
+}
@@ -0,0 +1,2177 @@
+use super::Instructions;
+use super::RunState;
+use super::Status;
+use super::IRQ;
+use crate::bus::Bus;
+
+pub(super) const fn initializeInstructions<B: Bus>() -> Instructions<B> {
+    let mut instructions: Instructions<B> = [None; 256];
+
+    //     @instruction(name="BRK", mode="imp", cycles=7)
+    instructions[0x00] = Some(|self2| {
+        // pc has already been increased one, BRK skips a padding byte
+        self2.DummyRead(self2.pc);
+        let pc = self2.pc.wrapping_add(1);
+        self2.stPushWord(pc);
+
+        self2.p |= Status::BREAK;
+        self2.stPush((self2.p | Status::BREAK | Status::UNUSED).bits());
+
+        self2.p |= Status::INTERRUPT;
+        self2.pc = self2.WordAt(IRQ);
+    });
+    //     @instruction(name="BPL", mode="rel", cycles=2, extracycles=2)
+    instructions[0x10] = Some(|self2| {
+        self2.opBCL(Status::NEGATIVE);
+    });
+    //     @instruction(name="CLC", mode="imp", cycles=2)
+    instructions[0x18] = Some(|self2| {
+        self2.opCLR(Status::CARRY);
+    });
+    //     @instruction(name="JSR", mode="abs", cycles=6)
+    instructions[0x20] = Some(|self2| {
+        // the high byte of the target is read after pc is pushed
+        let lo = self2.ByteAt(self2.pc);
+        self2.DummyRead(self2.StackAddr());
+        self2.stPushWord(self2.pc.wrapping_add(1));
+        self2.pc = lo as u16 | ((self2.ByteAt(self2.pc.wrapping_add(1)) as u16) << 8);
+    });
+    //     @instruction(name="SEC", mode="imp", cycles=2)
+    instructions[0x38] = Some(|self2| {
+        self2.opSET(Status::CARRY);
+    });
+    //     @instruction(name="EOR", mode="inx", cycles=6)
+    instructions[0x41] = Some(|self2| {
+        let xAddr = self2.IndirectXAddr();
+        self2.opEOR(xAddr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="PHA", mode="imp", cycles=3)
+    instructions[0x48] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.stPush(self2.acc);
+    });
+    //     @instruction(name="JMP", mode="abs", cycles=3)
+    instructions[0x4c] = Some(|self2| {
+        self2.pc = self2.WordAt(self2.pc);
+    });
+    //     @instruction(name="BVC", mode="rel", cycles=2, extracycles=2)
+    instructions[0x50] = Some(|self2| {
+        self2.opBCL(Status::OVERFLOW);
+    });
+    //     @instruction(name="EOR", mode="zpx", cycles=4)
+    instructions[0x55] = Some(|self2| {
+        let zpXAddr = self2.ZeroPageXAddr();
+        self2.opEOR(zpXAddr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="EOR", mode="aby", cycles=4, extracycles=1)
+    instructions[0x59] = Some(|self2| {
+        let absXAddr = self2.AbsoluteYAddr();
+        self2.opEOR(absXAddr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="ADC", mode="zpg", cycles=3)
+    instructions[0x65] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opADC(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="PLA", mode="imp", cycles=4)
+    instructions[0x68] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.DummyRead(self2.StackAddr());
+        self2.acc = self2.stPop();
+        self2.FlagsNZ(self2.acc);
+    });
+    //     @instruction(name="JMP", mode="ind", cycles=5)
+    instructions[0x6c] = Some(|self2| {
+        let ta = self2.WordAt(self2.pc);
+        self2.pc = self2.WrapAt(ta);
+    });
+    //     @instruction(name="ADC", mode="iny", cycles=5, extracycles=1)
+    instructions[0x71] = Some(|self2| {
+        let addr = self2.IndirectYAddr();
+        self2.opADC(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="ADC", mode="aby", cycles=4, extracycles=1)
+    instructions[0x79] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opADC(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="STA", mode="inx", cycles=6)
+    instructions[0x81] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opSTA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="STA", mode="zpg", cycles=3)
+    instructions[0x85] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSTA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="DEY", mode="imp", cycles=2)
+    instructions[0x88] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.y = self2.y.wrapping_sub(1);
+        self2.FlagsNZ(self2.y);
+    });
+    //     @instruction(name="STA", mode="abs", cycles=4)
+    instructions[0x8d] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opSTA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="BCC", mode="rel", cycles=2, extracycles=2)
+    instructions[0x90] = Some(|self2| {
+        self2.opBCL(Status::CARRY);
+    });
+    //     @instruction(name="STA", mode="zpx", cycles=4)
+    instructions[0x95] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opSTA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="STA", mode="aby", cycles=5)
+    instructions[0x99] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opSTA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="STA", mode="abx", cycles=5)
+    instructions[0x9d] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opSTA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="LDA", mode="inx", cycles=6)
+    instructions[0xa1] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opLDA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="LDY", mode="zpg", cycles=3)
+    instructions[0xa4] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opLDY(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="LDX", mode="zpg", cycles=3)
+    instructions[0xa6] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opLDX(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="LDA", mode="imm", cycles=2)
+    instructions[0xa9] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opLDA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="LDY", mode="abs", cycles=4)
+    instructions[0xac] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opLDY(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="LDX", mode="abs", cycles=4)
+    instructions[0xae] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opLDX(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="LDA", mode="iny", cycles=5, extracycles=1)
+    instructions[0xb1] = Some(|self2| {
+        let addr = self2.IndirectYAddr();
+        self2.opLDA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="LDA", mode="zpx", cycles=4)
+    instructions[0xb5] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opLDA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="LDA", mode="aby", cycles=4, extracycles=1)
+    instructions[0xb9] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opLDA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="LDY", mode="abx", cycles=4, extracycles=1)
+    instructions[0xbc] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opLDY(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="LDX", mode="aby", cycles=4, extracycles=1)
+    instructions[0xbe] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opLDX(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="CMP", mode="inx", cycles=6)
+    instructions[0xc1] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opCMPR(addr, self2.acc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="CMP", mode="zpg", cycles=3)
+    instructions[0xc5] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opCMPR(addr, self2.acc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="INY", mode="imp", cycles=2)
+    instructions[0xc8] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.y = self2.y.wrapping_add(1);
+        self2.FlagsNZ(self2.y);
+    });
+    //     @instruction(name="DEX", mode="imp", cycles=2)
+    instructions[0xca] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.x = self2.x.wrapping_sub(1);
+        self2.FlagsNZ(self2.x);
+    });
+    //     @instruction(name="CMP", mode="abs", cycles=4)
+    instructions[0xcd] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opCMPR(addr, self2.acc);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="BNE", mode="rel", cycles=2, extracycles=2)
+    instructions[0xd0] = Some(|self2| {
+        self2.opBCL(Status::ZERO);
+    });
+    //     @instruction(name="CMP", mode="zpx", cycles=4)
+    instructions[0xd5] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opCMPR(addr, self2.acc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="CMP", mode="aby", cycles=4, extracycles=1)
+    instructions[0xd9] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opCMPR(addr, self2.acc);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="CPX", mode="imm", cycles=2)
+    instructions[0xe0] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opCMPR(addr, self2.x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="CPX", mode="zpg", cycles=3)
+    instructions[0xe4] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opCMPR(addr, self2.x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="INX", mode="imp", cycles=2)
+    instructions[0xe8] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.x = self2.x.wrapping_add(1);
+        self2.FlagsNZ(self2.x);
+    });
+    //     @instruction(name="CPX", mode="abs", cycles=4)
+    instructions[0xec] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opCMPR(addr, self2.x);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="SBC", mode="iny", cycles=5, extracycles=1)
+    instructions[0xf1] = Some(|self2| {
+        let addr = self2.IndirectYAddr();
+        self2.opSBC(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="SBC", mode="aby", cycles=4, extracycles=1)
+    instructions[0xf9] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opSBC(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="SEI", mode="imp", cycles=2)
+    instructions[0x78] = Some(|self2| self2.opSET(Status::INTERRUPT));
+
+    //     @instruction(name="ASL", mode="zpg", cycles=5)
+    instructions[0x06] = Some(|self2| {
+        let zero_page_addr = self2.ZeroPageAddr();
+
+        self2.opASL(Some(zero_page_addr));
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="ASL", mode="acc", cycles=2)
+    instructions[0x0a] = Some(|self2| {
+        self2.opASL(None);
+    });
+    //     @instruction(name="ASL", mode="abs", cycles=6)
+    instructions[0x0e] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opASL(Some(addr));
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="ASL", mode="zpx", cycles=6)
+    instructions[0x16] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opASL(Some(addr));
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="ASL", mode="abx", cycles=7)
+    instructions[0x1e] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opASL(Some(addr));
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="ROL", mode="zpg", cycles=5)
+    instructions[0x26] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opROL(Some(addr));
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="ROL", mode="acc", cycles=2)
+    instructions[0x2a] = Some(|self2| {
+        self2.opROL(None);
+    });
+    //     @instruction(name="ROL", mode="abs", cycles=6)
+    instructions[0x2e] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opROL(Some(addr));
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="ROL", mode="zpx", cycles=6)
+    instructions[0x36] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opROL(Some(addr));
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ROL", mode="abx", cycles=7)
+    instructions[0x3e] = Some(|self2| {
+        let x = self2.AbsoluteXAddr();
+        self2.opROL(Some(x));
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+    //     @instruction(name="EOR", mode="zpg", cycles=3)
+    instructions[0x45] = Some(|self2| {
+        let x = self2.ZeroPageAddr();
+        self2.opEOR(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="EOR", mode="imm", cycles=2)
+    instructions[0x49] = Some(|self2| {
+        let x = self2.ProgramCounter();
+        self2.opEOR(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="EOR", mode="abs", cycles=4)
+    instructions[0x4d] = Some(|self2| {
+        let x = self2.AbsoluteAddr();
+        self2.opEOR(x);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="EOR", mode="iny", cycles=5, extracycles=1)
+    instructions[0x51] = Some(|self2| {
+        let x = self2.IndirectYAddr();
+        self2.opEOR(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="EOR", mode="abx", cycles=4, extracycles=1)
+    instructions[0x5d] = Some(|self2| {
+        let x = self2.AbsoluteXAddr();
+        self2.opEOR(x);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="ADC", mode="inx", cycles=6)
+    instructions[0x61] = Some(|self2| {
+        let x = self2.IndirectXAddr();
+        self2.opADC(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="ADC", mode="imm", cycles=2)
+    instructions[0x69] = Some(|self2| {
+        let x = self2.ProgramCounter();
+        self2.opADC(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="ADC", mode="abs", cycles=4)
+    instructions[0x6d] = Some(|self2| {
+        let x = self2.AbsoluteAddr();
+        self2.opADC(x);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="ADC", mode="zpx", cycles=4)
+    instructions[0x75] = Some(|self2| {
+        let x = self2.ZeroPageXAddr();
+        self2.opADC(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    //     @instruction(name="ADC", mode="abx", cycles=4, extracycles=1)
+    instructions[0x7d] = Some(|self2| {
+        let x = self2.AbsoluteXAddr();
+        self2.opADC(x);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="TXA", mode="imp", cycles=2)
+    instructions[0x8a] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.acc = self2.x;
+        self2.FlagsNZ(self2.acc);
+    });
+
+    //     @instruction(name="STA", mode="iny", cycles=6)
+    instructions[0x91] = Some(|self2| {
+        let x = self2.IndirectYAddr();
+        self2.opSTA(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LDY", mode="imm", cycles=2)
+    instructions[0xa0] = Some(|self2| {
+        let x = self2.ProgramCounter();
+        self2.opLDY(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LDX", mode="imm", cycles=2)
+    instructions[0xa2] = Some(|self2| {
+        let y = self2.ProgramCounter();
+        self2.opLDX(y);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LDA", mode="zpg", cycles=3)
+    instructions[0xa5] = Some(|self2| {
+        let x = self2.ZeroPageAddr();
+        self2.opLDA(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="TAX", mode="imp", cycles=2)
+    instructions[0xaa] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.x = self2.acc;
+        self2.FlagsNZ(self2.x);
+    });
+
+    //     @instruction(name="LDA", mode="abs", cycles=4)
+    instructions[0xad] = Some(|self2| {
+        let x = self2.AbsoluteAddr();
+        self2.opLDA(x);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="BCS", mode="rel", cycles=2, extracycles=2)
+    instructions[0xb0] = Some(|self2| {
+        self2.opBST(Status::CARRY);
+    });
+
+    //     @instruction(name="LDY", mode="zpx", cycles=4)
+    instructions[0xb4] = Some(|self2| {
+        let x = self2.ZeroPageXAddr();
+        self2.opLDY(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LDX", mode="zpy", cycles=4)
+    instructions[0xb6] = Some(|self2| {
+        let y = self2.ZeroPageYAddr();
+        self2.opLDX(y);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LDA", mode="abx", cycles=4, extracycles=1)
+    instructions[0xbd] = Some(|self2| {
+        let x = self2.AbsoluteXAddr();
+        self2.opLDA(x);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="CMP", mode="imm", cycles=2)
+    instructions[0xc9] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opCMPR(addr, self2.acc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="CMP", mode="iny", cycles=5, extracycles=1)
+    instructions[0xd1] = Some(|self2| {
+            let addr = self2.IndirectYAddr();
+            self2.opCMPR(addr, self2.acc);
+            self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="CMP", mode="abx", cycles=4, extracycles=1)
+    instructions[0xdd] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opCMPR(addr, self2.acc);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="SBC", mode="inx", cycles=6)
+    instructions[0xe1] = Some(|self2| {
+        let x = self2.IndirectXAddr();
+        self2.opSBC(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SBC", mode="zpg", cycles=3)
+    instructions[0xe5] = Some(|self2| {
+        let x = self2.ZeroPageAddr();
+        self2.opSBC(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SBC", mode="imm", cycles=2)
+    instructions[0xe9] = Some(|self2| {
+        let x = self2.ProgramCounter();
+        self2.opSBC(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SBC", mode="abs", cycles=4)
+    instructions[0xed] = Some(|self2| {
+        let x = self2.AbsoluteAddr();
+        self2.opSBC(x);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="SBC", mode="zpx", cycles=4)
+    instructions[0xf5] = Some(|self2| {
+        let x = self2.ZeroPageXAddr();
+        self2.opSBC(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SBC", mode="abx", cycles=4, extracycles=1)
+    instructions[0xfd] = Some(|self2| {
+        let x = self2.AbsoluteXAddr();
+        self2.opSBC(x);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="RTI", mode="imp", cycles=6)
+    instructions[0x40] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.DummyRead(self2.StackAddr());
+        self2.p = Status::fromBits(self2.stPop()) | Status::BREAK | Status::UNUSED;
+        self2.pc = self2.stPopWord()
+    });
+
+    //     @instruction(name="RTS", mode="imp", cycles=6)
+    instructions[0x60] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.DummyRead(self2.StackAddr());
+        self2.pc = self2.stPopWord();
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1)
+    });
+
+    //     @instruction(name="TXS", mode="imp", cycles=2)
+    instructions[0x9a] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.sp = self2.x
+    });
+    //     @instruction(name="TSX", mode="imp", cycles=2)
+    instructions[0xba] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.x = self2.sp;
+        self2.FlagsNZ(self2.x);
+    });
+    //     @instruction(name="CLD", mode="imp", cycles=2)
+    instructions[0xd8] = Some(|self2| {
+        self2.opCLR(Status::DECIMAL);
+    });
+
+    //     @instruction(name="TYA", mode="imp", cycles=2)
+    instructions[0x98] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.acc = self2.y;
+        self2.FlagsNZ(self2.acc);
+    });
+    
+    //     @instruction(name="TAY", mode="imp", cycles=2)
+    instructions[0xa8] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.y = self2.acc;
+        self2.FlagsNZ(self2.y);
+    });
+    
+    //     @instruction(name="BEQ", mode="rel", cycles=2, extracycles=2)
+    instructions[0xf0] = Some(|self2| {
+        self2.opBST(Status::ZERO);
+    });
+    
+    //     @instruction(name="CPY", mode="imm", cycles=2)
+    instructions[0xc0] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opCMPR(addr, self2.y);
+        self2.pc = self2.pc.wrapping_add(1)
+    });
+
+    //     @instruction(name="INC", mode="zpg", cycles=5)
+    instructions[0xe6] = Some(|self2| {
+        let x = self2.ZeroPageAddr();
+        self2.opINCR(Some(x));
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    
+    //     @instruction(name="DEC", mode="zpg", cycles=5)
+    instructions[0xc6] = Some(|self2| {
+        let x = self2.ZeroPageAddr();
+        self2.opDECR(Some(x));
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="STX", mode="zpg", cycles=3)
+    instructions[0x86] = Some(|self2| {
+        let y = self2.ZeroPageAddr();
+        self2.opSTX(y);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ORA", mode="imm", cycles=2)
+    instructions[0x09] = Some(|self2| {
+        let x = self2.ProgramCounter();
+        self2.opORA(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+    
+    //     @instruction(name="STY", mode="zpg", cycles=3)
+    instructions[0x84] = Some(|self2| {
+        let x = self2.ZeroPageAddr();
+        self2.opSTY(x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+       
+        
+    // ________________________________________________ remaining NMOS 6502 documented opcodes
+
+    //     @instruction(name="ORA", mode="inx", cycles=6)
+    instructions[0x01] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opORA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ORA", mode="zpg", cycles=3)
+    instructions[0x05] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opORA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ORA", mode="abs", cycles=4)
+    instructions[0x0d] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opORA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="ORA", mode="iny", cycles=5, extracycles=1)
+    instructions[0x11] = Some(|self2| {
+        let addr = self2.IndirectYAddr();
+        self2.opORA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ORA", mode="zpx", cycles=4)
+    instructions[0x15] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opORA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ORA", mode="aby", cycles=4, extracycles=1)
+    instructions[0x19] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opORA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="ORA", mode="abx", cycles=4, extracycles=1)
+    instructions[0x1d] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opORA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="AND", mode="inx", cycles=6)
+    instructions[0x21] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opAND(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="AND", mode="zpg", cycles=3)
+    instructions[0x25] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opAND(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="AND", mode="imm", cycles=2)
+    instructions[0x29] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opAND(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="AND", mode="abs", cycles=4)
+    instructions[0x2d] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opAND(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="AND", mode="iny", cycles=5, extracycles=1)
+    instructions[0x31] = Some(|self2| {
+        let addr = self2.IndirectYAddr();
+        self2.opAND(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="AND", mode="zpx", cycles=4)
+    instructions[0x35] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opAND(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="AND", mode="aby", cycles=4, extracycles=1)
+    instructions[0x39] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opAND(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="AND", mode="abx", cycles=4, extracycles=1)
+    instructions[0x3d] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opAND(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="BIT", mode="zpg", cycles=3)
+    instructions[0x24] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opBIT(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="BIT", mode="abs", cycles=4)
+    instructions[0x2c] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opBIT(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="LSR", mode="zpg", cycles=5)
+    instructions[0x46] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opLSR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LSR", mode="acc", cycles=2)
+    instructions[0x4a] = Some(|self2| {
+        self2.opLSR(None);
+    });
+
+    //     @instruction(name="LSR", mode="abs", cycles=6)
+    instructions[0x4e] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opLSR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="LSR", mode="zpx", cycles=6)
+    instructions[0x56] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opLSR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LSR", mode="abx", cycles=7)
+    instructions[0x5e] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opLSR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="ROR", mode="zpg", cycles=5)
+    instructions[0x66] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opROR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ROR", mode="acc", cycles=2)
+    instructions[0x6a] = Some(|self2| {
+        self2.opROR(None);
+    });
+
+    //     @instruction(name="ROR", mode="abs", cycles=6)
+    instructions[0x6e] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opROR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="ROR", mode="zpx", cycles=6)
+    instructions[0x76] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opROR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ROR", mode="abx", cycles=7)
+    instructions[0x7e] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opROR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="STY", mode="abs", cycles=4)
+    instructions[0x8c] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opSTY(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="STX", mode="abs", cycles=4)
+    instructions[0x8e] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opSTX(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="STY", mode="zpx", cycles=4)
+    instructions[0x94] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opSTY(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="STX", mode="zpy", cycles=4)
+    instructions[0x96] = Some(|self2| {
+        let addr = self2.ZeroPageYAddr();
+        self2.opSTX(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="CPY", mode="zpg", cycles=3)
+    instructions[0xc4] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opCMPR(addr, self2.y);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="CPY", mode="abs", cycles=4)
+    instructions[0xcc] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opCMPR(addr, self2.y);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="DEC", mode="abs", cycles=6)
+    instructions[0xce] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opDECR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="DEC", mode="zpx", cycles=6)
+    instructions[0xd6] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opDECR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="DEC", mode="abx", cycles=7)
+    instructions[0xde] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opDECR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="INC", mode="abs", cycles=6)
+    instructions[0xee] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opINCR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="INC", mode="zpx", cycles=6)
+    instructions[0xf6] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opINCR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="INC", mode="abx", cycles=7)
+    instructions[0xfe] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opINCR(Some(addr));
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="BMI", mode="rel", cycles=2, extracycles=2)
+    instructions[0x30] = Some(|self2| {
+        self2.opBST(Status::NEGATIVE);
+    });
+
+    //     @instruction(name="BVS", mode="rel", cycles=2, extracycles=2)
+    instructions[0x70] = Some(|self2| {
+        self2.opBST(Status::OVERFLOW);
+    });
+
+    //     @instruction(name="PHP", mode="imp", cycles=3)
+    instructions[0x08] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.stPush((self2.p | Status::BREAK | Status::UNUSED).bits());
+    });
+
+    //     @instruction(name="PLP", mode="imp", cycles=4)
+    instructions[0x28] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.DummyRead(self2.StackAddr());
+        self2.p = Status::fromBits(self2.stPop()) | Status::BREAK | Status::UNUSED;
+    });
+
+    //     @instruction(name="CLI", mode="imp", cycles=2)
+    instructions[0x58] = Some(|self2| {
+        self2.opCLR(Status::INTERRUPT);
+    });
+
+    //     @instruction(name="CLV", mode="imp", cycles=2)
+    instructions[0xb8] = Some(|self2| {
+        self2.opCLR(Status::OVERFLOW);
+    });
+
+    //     @instruction(name="SED", mode="imp", cycles=2)
+    instructions[0xf8] = Some(|self2| {
+        self2.opSET(Status::DECIMAL);
+    });
+
+    //     @instruction(name="NOP", mode="imp", cycles=2)
+    instructions[0xea] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+    });
+
+    instructions
+}
+
+// The NMOS 6502 decodes every opcode, the undocumented ones included. The
+// stable ones are listed first, then the unstable ANE/LXA/SHA/SHX/SHY/TAS.
+// The JAM opcodes that lock up the chip are left out.
+pub(super) const fn initializeNmosInstructions<B: Bus>() -> Instructions<B> {
+    let mut instructions = initializeInstructions();
+
+    //     @instruction(name="SLO", mode="inx", cycles=8)
+    instructions[0x03] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opSLO(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SLO", mode="zpg", cycles=5)
+    instructions[0x07] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSLO(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SLO", mode="abs", cycles=6)
+    instructions[0x0f] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opSLO(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="SLO", mode="iny", cycles=8)
+    instructions[0x13] = Some(|self2| {
+        let addr = self2.IndirectYAddr();
+        self2.opSLO(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SLO", mode="zpx", cycles=6)
+    instructions[0x17] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opSLO(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SLO", mode="aby", cycles=7)
+    instructions[0x1b] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opSLO(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="SLO", mode="abx", cycles=7)
+    instructions[0x1f] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opSLO(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="RLA", mode="inx", cycles=8)
+    instructions[0x23] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opRLA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RLA", mode="zpg", cycles=5)
+    instructions[0x27] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opRLA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RLA", mode="abs", cycles=6)
+    instructions[0x2f] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opRLA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="RLA", mode="iny", cycles=8)
+    instructions[0x33] = Some(|self2| {
+        let addr = self2.IndirectYAddr();
+        self2.opRLA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RLA", mode="zpx", cycles=6)
+    instructions[0x37] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opRLA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RLA", mode="aby", cycles=7)
+    instructions[0x3b] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opRLA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="RLA", mode="abx", cycles=7)
+    instructions[0x3f] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opRLA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="SRE", mode="inx", cycles=8)
+    instructions[0x43] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opSRE(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SRE", mode="zpg", cycles=5)
+    instructions[0x47] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSRE(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SRE", mode="abs", cycles=6)
+    instructions[0x4f] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opSRE(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="SRE", mode="iny", cycles=8)
+    instructions[0x53] = Some(|self2| {
+        let addr = self2.IndirectYAddr();
+        self2.opSRE(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SRE", mode="zpx", cycles=6)
+    instructions[0x57] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opSRE(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SRE", mode="aby", cycles=7)
+    instructions[0x5b] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opSRE(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="SRE", mode="abx", cycles=7)
+    instructions[0x5f] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opSRE(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="RRA", mode="inx", cycles=8)
+    instructions[0x63] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opRRA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RRA", mode="zpg", cycles=5)
+    instructions[0x67] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opRRA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RRA", mode="abs", cycles=6)
+    instructions[0x6f] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opRRA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="RRA", mode="iny", cycles=8)
+    instructions[0x73] = Some(|self2| {
+        let addr = self2.IndirectYAddr();
+        self2.opRRA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RRA", mode="zpx", cycles=6)
+    instructions[0x77] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opRRA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RRA", mode="aby", cycles=7)
+    instructions[0x7b] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opRRA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="RRA", mode="abx", cycles=7)
+    instructions[0x7f] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opRRA(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="DCP", mode="inx", cycles=8)
+    instructions[0xc3] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opDCP(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="DCP", mode="zpg", cycles=5)
+    instructions[0xc7] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opDCP(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="DCP", mode="abs", cycles=6)
+    instructions[0xcf] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opDCP(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="DCP", mode="iny", cycles=8)
+    instructions[0xd3] = Some(|self2| {
+        let addr = self2.IndirectYAddr();
+        self2.opDCP(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="DCP", mode="zpx", cycles=6)
+    instructions[0xd7] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opDCP(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="DCP", mode="aby", cycles=7)
+    instructions[0xdb] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opDCP(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="DCP", mode="abx", cycles=7)
+    instructions[0xdf] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opDCP(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="ISC", mode="inx", cycles=8)
+    instructions[0xe3] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opISC(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ISC", mode="zpg", cycles=5)
+    instructions[0xe7] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opISC(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ISC", mode="abs", cycles=6)
+    instructions[0xef] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opISC(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="ISC", mode="iny", cycles=8)
+    instructions[0xf3] = Some(|self2| {
+        let addr = self2.IndirectYAddr();
+        self2.opISC(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ISC", mode="zpx", cycles=6)
+    instructions[0xf7] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opISC(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ISC", mode="aby", cycles=7)
+    instructions[0xfb] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opISC(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="ISC", mode="abx", cycles=7)
+    instructions[0xff] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opISC(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="SAX", mode="inx", cycles=6)
+    instructions[0x83] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opSAX(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SAX", mode="zpg", cycles=3)
+    instructions[0x87] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSAX(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SAX", mode="abs", cycles=4)
+    instructions[0x8f] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opSAX(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="SAX", mode="zpy", cycles=4)
+    instructions[0x97] = Some(|self2| {
+        let addr = self2.ZeroPageYAddr();
+        self2.opSAX(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LAX", mode="inx", cycles=6)
+    instructions[0xa3] = Some(|self2| {
+        let addr = self2.IndirectXAddr();
+        self2.opLAX(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LAX", mode="zpg", cycles=3)
+    instructions[0xa7] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opLAX(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LAX", mode="abs", cycles=4)
+    instructions[0xaf] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opLAX(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="LAX", mode="iny", cycles=5, extracycles=1)
+    instructions[0xb3] = Some(|self2| {
+        let addr = self2.IndirectYAddr();
+        self2.opLAX(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LAX", mode="zpy", cycles=4)
+    instructions[0xb7] = Some(|self2| {
+        let addr = self2.ZeroPageYAddr();
+        self2.opLAX(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LAX", mode="aby", cycles=4, extracycles=1)
+    instructions[0xbf] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opLAX(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="ANC", mode="imm", cycles=2)
+    instructions[0x0b] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opANC(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ANC", mode="imm", cycles=2)
+    instructions[0x2b] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opANC(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ALR", mode="imm", cycles=2)
+    instructions[0x4b] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opALR(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ARR", mode="imm", cycles=2)
+    instructions[0x6b] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opARR(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SBX", mode="imm", cycles=2)
+    instructions[0xcb] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opSBX(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SBC", mode="imm", cycles=2)
+    instructions[0xeb] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opSBC(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LAS", mode="aby", cycles=4, extracycles=1)
+    instructions[0xbb] = Some(|self2| {
+        let addr = self2.AbsoluteYAddr();
+        self2.opLAS(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="NOP", mode="imp", cycles=2)
+    instructions[0x1a] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+    });
+
+    //     @instruction(name="NOP", mode="imp", cycles=2)
+    instructions[0x3a] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+    });
+
+    //     @instruction(name="NOP", mode="imp", cycles=2)
+    instructions[0x5a] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+    });
+
+    //     @instruction(name="NOP", mode="imp", cycles=2)
+    instructions[0x7a] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+    });
+
+    //     @instruction(name="NOP", mode="imp", cycles=2)
+    instructions[0xda] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+    });
+
+    //     @instruction(name="NOP", mode="imp", cycles=2)
+    instructions[0xfa] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+    });
+
+    //     @instruction(name="NOP", mode="imm", cycles=2)
+    instructions[0x80] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="imm", cycles=2)
+    instructions[0x82] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="imm", cycles=2)
+    instructions[0x89] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="imm", cycles=2)
+    instructions[0xc2] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="imm", cycles=2)
+    instructions[0xe2] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpg", cycles=3)
+    instructions[0x04] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpg", cycles=3)
+    instructions[0x44] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpg", cycles=3)
+    instructions[0x64] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpx", cycles=4)
+    instructions[0x14] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpx", cycles=4)
+    instructions[0x34] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpx", cycles=4)
+    instructions[0x54] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpx", cycles=4)
+    instructions[0x74] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpx", cycles=4)
+    instructions[0xd4] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpx", cycles=4)
+    instructions[0xf4] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="abs", cycles=4)
+    instructions[0x0c] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="NOP", mode="abx", cycles=4, extracycles=1)
+    instructions[0x1c] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="NOP", mode="abx", cycles=4, extracycles=1)
+    instructions[0x3c] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="NOP", mode="abx", cycles=4, extracycles=1)
+    instructions[0x5c] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="NOP", mode="abx", cycles=4, extracycles=1)
+    instructions[0x7c] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="NOP", mode="abx", cycles=4, extracycles=1)
+    instructions[0xdc] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="NOP", mode="abx", cycles=4, extracycles=1)
+    instructions[0xfc] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    // ________________________________________________ unstable NMOS opcodes
+    //     @instruction(name="ANE", mode="imm", cycles=2)
+    instructions[0x8b] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opANE(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LXA", mode="imm", cycles=2)
+    instructions[0xab] = Some(|self2| {
+        let addr = self2.ProgramCounter();
+        self2.opLXA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SHA", mode="iny", cycles=6)
+    instructions[0x93] = Some(|self2| {
+        let zp = self2.ZeroPageAddr();
+        let base = self2.WrapAt(zp);
+        self2.opSHx(base, self2.y, self2.acc & self2.x);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SHA", mode="aby", cycles=5)
+    instructions[0x9f] = Some(|self2| {
+        let base = self2.AbsoluteAddr();
+        self2.opSHx(base, self2.y, self2.acc & self2.x);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="TAS", mode="aby", cycles=5)
+    instructions[0x9b] = Some(|self2| {
+        self2.sp = self2.acc & self2.x;
+        let base = self2.AbsoluteAddr();
+        self2.opSHx(base, self2.y, self2.sp);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="SHY", mode="abx", cycles=5)
+    instructions[0x9c] = Some(|self2| {
+        let base = self2.AbsoluteAddr();
+        self2.opSHx(base, self2.x, self2.y);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="SHX", mode="aby", cycles=5)
+    instructions[0x9e] = Some(|self2| {
+        let base = self2.AbsoluteAddr();
+        self2.opSHx(base, self2.y, self2.x);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    instructions
+}
+
+// The CMOS parts keep every documented NMOS opcode and fill in some of the
+// unused slots, so their tables start from the 6502 one. See:
+// https://github.com/ucsbieee/py65/blob/main/py65/devices/mpu65c02.py
+
+// The 65SC02 is the CMOS core without the Rockwell bit instructions
+pub(super) const fn initialize65SC02Instructions<B: Bus>() -> Instructions<B> {
+    // ASL, LSR, ROL and ROR abs,X keep their NMOS handlers but only take
+    // 6 cycles, plus one on a page cross. CMOS_CYCLES has the right counts.
+    let mut instructions = initializeInstructions();
+
+    //     @instruction(name="BRK", mode="imp", cycles=7)
+    instructions[0x00] = Some(|self2| {
+        // pc has already been increased one, BRK skips a padding byte
+        self2.DummyRead(self2.pc);
+        let pc = self2.pc.wrapping_add(1);
+        self2.stPushWord(pc);
+
+        self2.p |= Status::BREAK;
+        self2.stPush((self2.p | Status::BREAK | Status::UNUSED).bits());
+
+        self2.p |= Status::INTERRUPT;
+        self2.p &= !Status::DECIMAL;
+        self2.pc = self2.WordAt(IRQ);
+    });
+
+    //     @instruction(name="ORA", mode="zpi", cycles=5)
+    instructions[0x12] = Some(|self2| {
+        let addr = self2.ZeroPageIndirectAddr();
+        self2.opORA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="AND", mode="zpi", cycles=5)
+    instructions[0x32] = Some(|self2| {
+        let addr = self2.ZeroPageIndirectAddr();
+        self2.opAND(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="EOR", mode="zpi", cycles=5)
+    instructions[0x52] = Some(|self2| {
+        let addr = self2.ZeroPageIndirectAddr();
+        self2.opEOR(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="ADC", mode="zpi", cycles=5)
+    instructions[0x72] = Some(|self2| {
+        let addr = self2.ZeroPageIndirectAddr();
+        self2.opADC(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="STA", mode="zpi", cycles=5)
+    instructions[0x92] = Some(|self2| {
+        let addr = self2.ZeroPageIndirectAddr();
+        self2.opSTA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="LDA", mode="zpi", cycles=5)
+    instructions[0xb2] = Some(|self2| {
+        let addr = self2.ZeroPageIndirectAddr();
+        self2.opLDA(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="CMP", mode="zpi", cycles=5)
+    instructions[0xd2] = Some(|self2| {
+        let addr = self2.ZeroPageIndirectAddr();
+        self2.opCMPR(addr, self2.acc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SBC", mode="zpi", cycles=5)
+    instructions[0xf2] = Some(|self2| {
+        let addr = self2.ZeroPageIndirectAddr();
+        self2.opSBC(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="TSB", mode="zpg", cycles=5)
+    instructions[0x04] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opTSB(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="TSB", mode="abs", cycles=6)
+    instructions[0x0c] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opTSB(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="TRB", mode="zpg", cycles=5)
+    instructions[0x14] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opTRB(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="TRB", mode="abs", cycles=6)
+    instructions[0x1c] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opTRB(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="BIT", mode="zpx", cycles=4)
+    instructions[0x34] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opBIT(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="BIT", mode="abx", cycles=4, extracycles=1)
+    instructions[0x3c] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opBIT(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="BIT", mode="imm", cycles=2)
+    instructions[0x89] = Some(|self2| {
+        // BIT immediate only changes the zero flag
+        let tbyte = self2.ImmediateByte();
+        self2.p &= !Status::ZERO;
+        if (self2.acc & tbyte) == 0 {
+            self2.p |= Status::ZERO;
+        }
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="STZ", mode="zpg", cycles=3)
+    instructions[0x64] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSTZ(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="STZ", mode="zpx", cycles=4)
+    instructions[0x74] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.opSTZ(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="STZ", mode="abs", cycles=4)
+    instructions[0x9c] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.opSTZ(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="STZ", mode="abx", cycles=5)
+    instructions[0x9e] = Some(|self2| {
+        let addr = self2.AbsoluteXAddr();
+        self2.opSTZ(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="INC", mode="acc", cycles=2)
+    instructions[0x1a] = Some(|self2| {
+        self2.opINCR(None);
+    });
+
+    //     @instruction(name="DEC", mode="acc", cycles=2)
+    instructions[0x3a] = Some(|self2| {
+        self2.opDECR(None);
+    });
+
+    //     @instruction(name="PHY", mode="imp", cycles=3)
+    instructions[0x5a] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.stPush(self2.y);
+    });
+
+    //     @instruction(name="PLY", mode="imp", cycles=4)
+    instructions[0x7a] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.DummyRead(self2.StackAddr());
+        self2.y = self2.stPop();
+        self2.FlagsNZ(self2.y);
+    });
+
+    //     @instruction(name="PHX", mode="imp", cycles=3)
+    instructions[0xda] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.stPush(self2.x);
+    });
+
+    //     @instruction(name="PLX", mode="imp", cycles=4)
+    instructions[0xfa] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.DummyRead(self2.StackAddr());
+        self2.x = self2.stPop();
+        self2.FlagsNZ(self2.x);
+    });
+
+    //     @instruction(name="JMP", mode="ind", cycles=6)
+    instructions[0x6c] = Some(|self2| {
+        // the 65C02 fixed the NMOS page wrap bug
+        let ta = self2.WordAt(self2.pc);
+        self2.DummyRead(self2.pc.wrapping_add(1));
+        self2.pc = self2.WordAt(ta);
+    });
+
+    //     @instruction(name="JMP", mode="iax", cycles=6)
+    instructions[0x7c] = Some(|self2| {
+        let ta = self2.IndirectAbsXAddr();
+        self2.DummyRead(self2.pc.wrapping_add(1));
+        self2.pc = self2.WordAt(ta);
+    });
+
+    //     @instruction(name="BRA", mode="rel", cycles=2, extracycles=2)
+    instructions[0x80] = Some(|self2| {
+        self2.BranchRelAddr();
+    });
+
+    // The unused CMOS opcodes are NOPs with the length and cycles of
+    // CMOS_CYCLES and the decode table. They read their operands but
+    // touch no registers or flags.
+
+    //     @instruction(name="NOP", mode="imm", cycles=2)
+    instructions[0x02] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="imm", cycles=2)
+    instructions[0x22] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="imm", cycles=2)
+    instructions[0x42] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="imm", cycles=2)
+    instructions[0x62] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="imm", cycles=2)
+    instructions[0x82] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="imm", cycles=2)
+    instructions[0xc2] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="imm", cycles=2)
+    instructions[0xe2] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpg", cycles=3)
+    instructions[0x44] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpx", cycles=4)
+    instructions[0x54] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpx", cycles=4)
+    instructions[0xd4] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="zpx", cycles=4)
+    instructions[0xf4] = Some(|self2| {
+        let addr = self2.ZeroPageXAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="NOP", mode="abs", cycles=8)
+    instructions[0x5c] = Some(|self2| {
+        // reads the operand address, then idles on the next byte
+        let addr = self2.AbsoluteAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+        self2.DummyRead(self2.pc);
+        self2.DummyRead(self2.pc);
+        self2.DummyRead(self2.pc);
+        self2.DummyRead(self2.pc);
+    });
+
+    //     @instruction(name="NOP", mode="abs", cycles=4)
+    instructions[0xdc] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    //     @instruction(name="NOP", mode="abs", cycles=4)
+    instructions[0xfc] = Some(|self2| {
+        let addr = self2.AbsoluteAddr();
+        self2.DummyRead(addr);
+        self2.pc = self2.pc.wrapping_add(2);
+    });
+
+    // The x3 and xb columns, and on the 65SC02 the x7 and xf ones the
+    // Rockwell table fills in, are single cycle NOPs: only the opcode
+    // fetch. WAI and STP replace 0xcb and 0xdb on the WDC part.
+    //     @instruction(name="NOP", mode="imp", cycles=1)
+    let mut opcode = 0x03;
+    while opcode < 0x100 {
+        instructions[opcode] = Some(|_| {});
+        instructions[opcode + 0x04] = Some(|_| {});
+        instructions[opcode + 0x08] = Some(|_| {});
+        instructions[opcode + 0x0c] = Some(|_| {});
+        opcode += 0x10;
+    }
+
+    instructions
+}
+
+// Rockwell added RMB/SMB/BBR/BBS on top of the 65SC02
+pub(super) const fn initializeR65C02Instructions<B: Bus>() -> Instructions<B> {
+    let mut instructions = initialize65SC02Instructions();
+    //     @instruction(name="RMB0", mode="zpg", cycles=5)
+    instructions[0x07] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opRMB(addr, 0xfe);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RMB1", mode="zpg", cycles=5)
+    instructions[0x17] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opRMB(addr, 0xfd);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RMB2", mode="zpg", cycles=5)
+    instructions[0x27] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opRMB(addr, 0xfb);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RMB3", mode="zpg", cycles=5)
+    instructions[0x37] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opRMB(addr, 0xf7);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RMB4", mode="zpg", cycles=5)
+    instructions[0x47] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opRMB(addr, 0xef);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RMB5", mode="zpg", cycles=5)
+    instructions[0x57] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opRMB(addr, 0xdf);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RMB6", mode="zpg", cycles=5)
+    instructions[0x67] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opRMB(addr, 0xbf);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="RMB7", mode="zpg", cycles=5)
+    instructions[0x77] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opRMB(addr, 0x7f);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SMB0", mode="zpg", cycles=5)
+    instructions[0x87] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSMB(addr, 0x01);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SMB1", mode="zpg", cycles=5)
+    instructions[0x97] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSMB(addr, 0x02);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SMB2", mode="zpg", cycles=5)
+    instructions[0xa7] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSMB(addr, 0x04);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SMB3", mode="zpg", cycles=5)
+    instructions[0xb7] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSMB(addr, 0x08);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SMB4", mode="zpg", cycles=5)
+    instructions[0xc7] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSMB(addr, 0x10);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SMB5", mode="zpg", cycles=5)
+    instructions[0xd7] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSMB(addr, 0x20);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SMB6", mode="zpg", cycles=5)
+    instructions[0xe7] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSMB(addr, 0x40);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="SMB7", mode="zpg", cycles=5)
+    instructions[0xf7] = Some(|self2| {
+        let addr = self2.ZeroPageAddr();
+        self2.opSMB(addr, 0x80);
+        self2.pc = self2.pc.wrapping_add(1);
+    });
+
+    //     @instruction(name="BBR0", mode="zpr", cycles=5, extracycles=2)
+    instructions[0x0f] = Some(|self2| {
+        self2.opBBR(0x01);
+    });
+
+    //     @instruction(name="BBR1", mode="zpr", cycles=5, extracycles=2)
+    instructions[0x1f] = Some(|self2| {
+        self2.opBBR(0x02);
+    });
+
+    //     @instruction(name="BBR2", mode="zpr", cycles=5, extracycles=2)
+    instructions[0x2f] = Some(|self2| {
+        self2.opBBR(0x04);
+    });
+
+    //     @instruction(name="BBR3", mode="zpr", cycles=5, extracycles=2)
+    instructions[0x3f] = Some(|self2| {
+        self2.opBBR(0x08);
+    });
+
+    //     @instruction(name="BBR4", mode="zpr", cycles=5, extracycles=2)
+    instructions[0x4f] = Some(|self2| {
+        self2.opBBR(0x10);
+    });
+
+    //     @instruction(name="BBR5", mode="zpr", cycles=5, extracycles=2)
+    instructions[0x5f] = Some(|self2| {
+        self2.opBBR(0x20);
+    });
+
+    //     @instruction(name="BBR6", mode="zpr", cycles=5, extracycles=2)
+    instructions[0x6f] = Some(|self2| {
+        self2.opBBR(0x40);
+    });
+
+    //     @instruction(name="BBR7", mode="zpr", cycles=5, extracycles=2)
+    instructions[0x7f] = Some(|self2| {
+        self2.opBBR(0x80);
+    });
+
+    //     @instruction(name="BBS0", mode="zpr", cycles=5, extracycles=2)
+    instructions[0x8f] = Some(|self2| {
+        self2.opBBS(0x01);
+    });
+
+    //     @instruction(name="BBS1", mode="zpr", cycles=5, extracycles=2)
+    instructions[0x9f] = Some(|self2| {
+        self2.opBBS(0x02);
+    });
+
+    //     @instruction(name="BBS2", mode="zpr", cycles=5, extracycles=2)
+    instructions[0xaf] = Some(|self2| {
+        self2.opBBS(0x04);
+    });
+
+    //     @instruction(name="BBS3", mode="zpr", cycles=5, extracycles=2)
+    instructions[0xbf] = Some(|self2| {
+        self2.opBBS(0x08);
+    });
+
+    //     @instruction(name="BBS4", mode="zpr", cycles=5, extracycles=2)
+    instructions[0xcf] = Some(|self2| {
+        self2.opBBS(0x10);
+    });
+
+    //     @instruction(name="BBS5", mode="zpr", cycles=5, extracycles=2)
+    instructions[0xdf] = Some(|self2| {
+        self2.opBBS(0x20);
+    });
+
+    //     @instruction(name="BBS6", mode="zpr", cycles=5, extracycles=2)
+    instructions[0xef] = Some(|self2| {
+        self2.opBBS(0x40);
+    });
+
+    //     @instruction(name="BBS7", mode="zpr", cycles=5, extracycles=2)
+    instructions[0xff] = Some(|self2| {
+        self2.opBBS(0x80);
+    });
+
+    instructions
+}
+
+// WDC's 65C02 is the Rockwell part plus WAI and STP
+pub(super) const fn initialize65C02Instructions<B: Bus>() -> Instructions<B> {
+    let mut instructions = initializeR65C02Instructions();
+
+    //     @instruction(name="WAI", mode="imp", cycles=3)
+    instructions[0xcb] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.DummyRead(self2.pc);
+        self2.runState = RunState::Waiting;
+    });
+
+    //     @instruction(name="STP", mode="imp", cycles=3)
+    instructions[0xdb] = Some(|self2| {
+        self2.DummyRead(self2.pc);
+        self2.DummyRead(self2.pc);
+        self2.runState = RunState::Stopped;
+    });
+
+    instructions
+}
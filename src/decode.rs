@@ -0,0 +1,288 @@
+// Base cycle count of every opcode, laid out like the opcode matrix (row is
+// the high nibble). Page crossings, taken branches and CMOS decimal mode add
+// to these through excycles. A 0 marks an opcode with no handler.
+// See: https://www.masswerk.at/6502/6502_instruction_set.html
+#[rustfmt::skip]
+const NMOS_CYCLES: [u8; 256] = [
+//  0  1  2  3  4  5  6  7  8  9  a  b  c  d  e  f
+    7, 6, 0, 8, 3, 3, 5, 5, 3, 2, 2, 2, 4, 4, 6, 6, // 0
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // 1
+    6, 6, 0, 8, 3, 3, 5, 5, 4, 2, 2, 2, 4, 4, 6, 6, // 2
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // 3
+    6, 6, 0, 8, 3, 3, 5, 5, 3, 2, 2, 2, 3, 4, 6, 6, // 4
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // 5
+    6, 6, 0, 8, 3, 3, 5, 5, 4, 2, 2, 2, 5, 4, 6, 6, // 6
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // 7
+    2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2, 4, 4, 4, 4, // 8
+    2, 6, 0, 6, 4, 4, 4, 4, 2, 5, 2, 5, 5, 5, 5, 5, // 9
+    2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2, 4, 4, 4, 4, // a
+    2, 5, 0, 5, 4, 4, 4, 4, 2, 4, 2, 4, 4, 4, 4, 4, // b
+    2, 6, 2, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6, // c
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // d
+    2, 6, 2, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6, // e
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // f
+];
+
+// The WDC 65C02 counts. The Rockwell and 65SC02 parts use the same ones,
+// the slots they leave out run as single cycle NOPs.
+#[rustfmt::skip]
+const CMOS_CYCLES: [u8; 256] = [
+//  0  1  2  3  4  5  6  7  8  9  a  b  c  d  e  f
+    7, 6, 2, 1, 5, 3, 5, 5, 3, 2, 2, 1, 6, 4, 6, 5, // 0
+    2, 5, 5, 1, 5, 4, 6, 5, 2, 4, 2, 1, 6, 4, 6, 5, // 1
+    6, 6, 2, 1, 3, 3, 5, 5, 4, 2, 2, 1, 4, 4, 6, 5, // 2
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 2, 1, 4, 4, 6, 5, // 3
+    6, 6, 2, 1, 3, 3, 5, 5, 3, 2, 2, 1, 3, 4, 6, 5, // 4
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 3, 1, 8, 4, 6, 5, // 5
+    6, 6, 2, 1, 3, 3, 5, 5, 4, 2, 2, 1, 6, 4, 6, 5, // 6
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 4, 1, 6, 4, 6, 5, // 7
+    2, 6, 2, 1, 3, 3, 3, 5, 2, 2, 2, 1, 4, 4, 4, 5, // 8
+    2, 6, 5, 1, 4, 4, 4, 5, 2, 5, 2, 1, 4, 5, 5, 5, // 9
+    2, 6, 2, 1, 3, 3, 3, 5, 2, 2, 2, 1, 4, 4, 4, 5, // a
+    2, 5, 5, 1, 4, 4, 4, 5, 2, 4, 2, 1, 4, 4, 4, 5, // b
+    2, 6, 2, 1, 3, 3, 5, 5, 2, 2, 2, 3, 4, 4, 6, 5, // c
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 3, 3, 4, 4, 7, 5, // d
+    2, 6, 2, 1, 3, 3, 5, 5, 2, 2, 2, 1, 4, 4, 6, 5, // e
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 4, 1, 4, 4, 7, 5, // f
+];
+
+// 1 where indexing across a page adds a cycle, py65's extracycles. Branches
+// always add their own penalties in BranchRelAddr.
+#[rustfmt::skip]
+const NMOS_EXTRACYCLES: [u8; 256] = [
+//  0  1  2  3  4  5  6  7  8  9  a  b  c  d  e  f
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 0
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, // 1
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 2
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, // 3
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 4
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, // 5
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 6
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, // 7
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 8
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 9
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // a
+    0, 1, 0, 1, 0, 0, 0, 0, 0, 1, 0, 1, 1, 1, 1, 1, // b
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // c
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, // d
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // e
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, // f
+];
+
+#[rustfmt::skip]
+const CMOS_EXTRACYCLES: [u8; 256] = [
+//  0  1  2  3  4  5  6  7  8  9  a  b  c  d  e  f
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 0
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 1, 0, // 1
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 2
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 1, 0, // 3
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 4
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 1, 0, // 5
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 6
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 1, 0, // 7
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 8
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 9
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // a
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 1, 0, // b
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // c
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, // d
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // e
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, // f
+];
+
+/// The addressing modes, named like py65's. Zpr is the zero page address
+/// and branch offset of BBR and BBS, Iax the (abs,X) of the 65C02 JMP.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressingMode {
+    Imp,
+    Acc,
+    Imm,
+    Zpg,
+    Zpx,
+    Zpy,
+    Abs,
+    Abx,
+    Aby,
+    Ind,
+    Inx,
+    Iny,
+    Rel,
+    Zpi,
+    Iax,
+    Zpr,
+}
+
+impl AddressingMode {
+    /// bytes an instruction takes, the opcode included
+    pub const fn length(self) -> u8 {
+        match self {
+            AddressingMode::Imp | AddressingMode::Acc => 1,
+            AddressingMode::Abs
+            | AddressingMode::Abx
+            | AddressingMode::Aby
+            | AddressingMode::Ind
+            | AddressingMode::Iax
+            | AddressingMode::Zpr => 3,
+            _ => 2,
+        }
+    }
+}
+
+/// One entry of a decode table. step() takes the cycle counts from here and
+/// the disassembler the mnemonic and mode, so they can't disagree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpcodeInfo {
+    pub mnemonic: &'static str,
+    pub mode: AddressingMode,
+    pub length: u8,
+    pub cycles: u8,
+    /// cycles added when indexing crosses a page
+    pub pagePenalty: u8,
+}
+
+// Mnemonic and addressing mode of every opcode, in the same layout as the
+// cycle counts. JAM marks the NMOS opcodes that lock up the chip, the
+// unused CMOS opcodes are NOPs of the length the chip skips.
+#[rustfmt::skip]
+const NMOS_MNEMONICS: [&str; 256] = [
+//  0      1      2      3      4      5      6      7      8      9      a      b      c      d      e      f
+    "BRK", "ORA", "JAM", "SLO", "NOP", "ORA", "ASL", "SLO", "PHP", "ORA", "ASL", "ANC", "NOP", "ORA", "ASL", "SLO", // 0
+    "BPL", "ORA", "JAM", "SLO", "NOP", "ORA", "ASL", "SLO", "CLC", "ORA", "NOP", "SLO", "NOP", "ORA", "ASL", "SLO", // 1
+    "JSR", "AND", "JAM", "RLA", "BIT", "AND", "ROL", "RLA", "PLP", "AND", "ROL", "ANC", "BIT", "AND", "ROL", "RLA", // 2
+    "BMI", "AND", "JAM", "RLA", "NOP", "AND", "ROL", "RLA", "SEC", "AND", "NOP", "RLA", "NOP", "AND", "ROL", "RLA", // 3
+    "RTI", "EOR", "JAM", "SRE", "NOP", "EOR", "LSR", "SRE", "PHA", "EOR", "LSR", "ALR", "JMP", "EOR", "LSR", "SRE", // 4
+    "BVC", "EOR", "JAM", "SRE", "NOP", "EOR", "LSR", "SRE", "CLI", "EOR", "NOP", "SRE", "NOP", "EOR", "LSR", "SRE", // 5
+    "RTS", "ADC", "JAM", "RRA", "NOP", "ADC", "ROR", "RRA", "PLA", "ADC", "ROR", "ARR", "JMP", "ADC", "ROR", "RRA", // 6
+    "BVS", "ADC", "JAM", "RRA", "NOP", "ADC", "ROR", "RRA", "SEI", "ADC", "NOP", "RRA", "NOP", "ADC", "ROR", "RRA", // 7
+    "NOP", "STA", "NOP", "SAX", "STY", "STA", "STX", "SAX", "DEY", "NOP", "TXA", "ANE", "STY", "STA", "STX", "SAX", // 8
+    "BCC", "STA", "JAM", "SHA", "STY", "STA", "STX", "SAX", "TYA", "STA", "TXS", "TAS", "SHY", "STA", "SHX", "SHA", // 9
+    "LDY", "LDA", "LDX", "LAX", "LDY", "LDA", "LDX", "LAX", "TAY", "LDA", "TAX", "LXA", "LDY", "LDA", "LDX", "LAX", // a
+    "BCS", "LDA", "JAM", "LAX", "LDY", "LDA", "LDX", "LAX", "CLV", "LDA", "TSX", "LAS", "LDY", "LDA", "LDX", "LAX", // b
+    "CPY", "CMP", "NOP", "DCP", "CPY", "CMP", "DEC", "DCP", "INY", "CMP", "DEX", "SBX", "CPY", "CMP", "DEC", "DCP", // c
+    "BNE", "CMP", "JAM", "DCP", "NOP", "CMP", "DEC", "DCP", "CLD", "CMP", "NOP", "DCP", "NOP", "CMP", "DEC", "DCP", // d
+    "CPX", "SBC", "NOP", "ISC", "CPX", "SBC", "INC", "ISC", "INX", "SBC", "NOP", "SBC", "CPX", "SBC", "INC", "ISC", // e
+    "BEQ", "SBC", "JAM", "ISC", "NOP", "SBC", "INC", "ISC", "SED", "SBC", "NOP", "ISC", "NOP", "SBC", "INC", "ISC", // f
+];
+
+#[rustfmt::skip]
+const CMOS_MNEMONICS: [&str; 256] = [
+//  0       1       2       3       4       5       6       7       8       9       a       b       c       d       e       f
+    "BRK",  "ORA",  "NOP",  "NOP",  "TSB",  "ORA",  "ASL",  "RMB0", "PHP",  "ORA",  "ASL",  "NOP",  "TSB",  "ORA",  "ASL",  "BBR0", // 0
+    "BPL",  "ORA",  "ORA",  "NOP",  "TRB",  "ORA",  "ASL",  "RMB1", "CLC",  "ORA",  "INC",  "NOP",  "TRB",  "ORA",  "ASL",  "BBR1", // 1
+    "JSR",  "AND",  "NOP",  "NOP",  "BIT",  "AND",  "ROL",  "RMB2", "PLP",  "AND",  "ROL",  "NOP",  "BIT",  "AND",  "ROL",  "BBR2", // 2
+    "BMI",  "AND",  "AND",  "NOP",  "BIT",  "AND",  "ROL",  "RMB3", "SEC",  "AND",  "DEC",  "NOP",  "BIT",  "AND",  "ROL",  "BBR3", // 3
+    "RTI",  "EOR",  "NOP",  "NOP",  "NOP",  "EOR",  "LSR",  "RMB4", "PHA",  "EOR",  "LSR",  "NOP",  "JMP",  "EOR",  "LSR",  "BBR4", // 4
+    "BVC",  "EOR",  "EOR",  "NOP",  "NOP",  "EOR",  "LSR",  "RMB5", "CLI",  "EOR",  "PHY",  "NOP",  "NOP",  "EOR",  "LSR",  "BBR5", // 5
+    "RTS",  "ADC",  "NOP",  "NOP",  "STZ",  "ADC",  "ROR",  "RMB6", "PLA",  "ADC",  "ROR",  "NOP",  "JMP",  "ADC",  "ROR",  "BBR6", // 6
+    "BVS",  "ADC",  "ADC",  "NOP",  "STZ",  "ADC",  "ROR",  "RMB7", "SEI",  "ADC",  "PLY",  "NOP",  "JMP",  "ADC",  "ROR",  "BBR7", // 7
+    "BRA",  "STA",  "NOP",  "NOP",  "STY",  "STA",  "STX",  "SMB0", "DEY",  "BIT",  "TXA",  "NOP",  "STY",  "STA",  "STX",  "BBS0", // 8
+    "BCC",  "STA",  "STA",  "NOP",  "STY",  "STA",  "STX",  "SMB1", "TYA",  "STA",  "TXS",  "NOP",  "STZ",  "STA",  "STZ",  "BBS1", // 9
+    "LDY",  "LDA",  "LDX",  "NOP",  "LDY",  "LDA",  "LDX",  "SMB2", "TAY",  "LDA",  "TAX",  "NOP",  "LDY",  "LDA",  "LDX",  "BBS2", // a
+    "BCS",  "LDA",  "LDA",  "NOP",  "LDY",  "LDA",  "LDX",  "SMB3", "CLV",  "LDA",  "TSX",  "NOP",  "LDY",  "LDA",  "LDX",  "BBS3", // b
+    "CPY",  "CMP",  "NOP",  "NOP",  "CPY",  "CMP",  "DEC",  "SMB4", "INY",  "CMP",  "DEX",  "WAI",  "CPY",  "CMP",  "DEC",  "BBS4", // c
+    "BNE",  "CMP",  "CMP",  "NOP",  "NOP",  "CMP",  "DEC",  "SMB5", "CLD",  "CMP",  "PHX",  "STP",  "NOP",  "CMP",  "DEC",  "BBS5", // d
+    "CPX",  "SBC",  "NOP",  "NOP",  "CPX",  "SBC",  "INC",  "SMB6", "INX",  "SBC",  "NOP",  "NOP",  "CPX",  "SBC",  "INC",  "BBS6", // e
+    "BEQ",  "SBC",  "SBC",  "NOP",  "NOP",  "SBC",  "INC",  "SMB7", "SED",  "SBC",  "PLX",  "NOP",  "NOP",  "SBC",  "INC",  "BBS7", // f
+];
+
+#[rustfmt::skip]
+const NMOS_MODES: [AddressingMode; 256] = {
+    use AddressingMode::*;
+    [
+//      0    1    2    3    4    5    6    7    8    9    a    b    c    d    e    f
+        Imp, Inx, Imp, Inx, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Acc, Imm, Abs, Abs, Abs, Abs, // 0
+        Rel, Iny, Imp, Iny, Zpx, Zpx, Zpx, Zpx, Imp, Aby, Imp, Aby, Abx, Abx, Abx, Abx, // 1
+        Abs, Inx, Imp, Inx, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Acc, Imm, Abs, Abs, Abs, Abs, // 2
+        Rel, Iny, Imp, Iny, Zpx, Zpx, Zpx, Zpx, Imp, Aby, Imp, Aby, Abx, Abx, Abx, Abx, // 3
+        Imp, Inx, Imp, Inx, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Acc, Imm, Abs, Abs, Abs, Abs, // 4
+        Rel, Iny, Imp, Iny, Zpx, Zpx, Zpx, Zpx, Imp, Aby, Imp, Aby, Abx, Abx, Abx, Abx, // 5
+        Imp, Inx, Imp, Inx, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Acc, Imm, Ind, Abs, Abs, Abs, // 6
+        Rel, Iny, Imp, Iny, Zpx, Zpx, Zpx, Zpx, Imp, Aby, Imp, Aby, Abx, Abx, Abx, Abx, // 7
+        Imm, Inx, Imm, Inx, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Imp, Imm, Abs, Abs, Abs, Abs, // 8
+        Rel, Iny, Imp, Iny, Zpx, Zpx, Zpy, Zpy, Imp, Aby, Imp, Aby, Abx, Abx, Aby, Aby, // 9
+        Imm, Inx, Imm, Inx, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Imp, Imm, Abs, Abs, Abs, Abs, // a
+        Rel, Iny, Imp, Iny, Zpx, Zpx, Zpy, Zpy, Imp, Aby, Imp, Aby, Abx, Abx, Aby, Aby, // b
+        Imm, Inx, Imm, Inx, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Imp, Imm, Abs, Abs, Abs, Abs, // c
+        Rel, Iny, Imp, Iny, Zpx, Zpx, Zpx, Zpx, Imp, Aby, Imp, Aby, Abx, Abx, Abx, Abx, // d
+        Imm, Inx, Imm, Inx, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Imp, Imm, Abs, Abs, Abs, Abs, // e
+        Rel, Iny, Imp, Iny, Zpx, Zpx, Zpx, Zpx, Imp, Aby, Imp, Aby, Abx, Abx, Abx, Abx, // f
+    ]
+};
+
+#[rustfmt::skip]
+const CMOS_MODES: [AddressingMode; 256] = {
+    use AddressingMode::*;
+    [
+//      0    1    2    3    4    5    6    7    8    9    a    b    c    d    e    f
+        Imp, Inx, Imm, Imp, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Acc, Imp, Abs, Abs, Abs, Zpr, // 0
+        Rel, Iny, Zpi, Imp, Zpg, Zpx, Zpx, Zpg, Imp, Aby, Acc, Imp, Abs, Abx, Abx, Zpr, // 1
+        Abs, Inx, Imm, Imp, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Acc, Imp, Abs, Abs, Abs, Zpr, // 2
+        Rel, Iny, Zpi, Imp, Zpx, Zpx, Zpx, Zpg, Imp, Aby, Acc, Imp, Abx, Abx, Abx, Zpr, // 3
+        Imp, Inx, Imm, Imp, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Acc, Imp, Abs, Abs, Abs, Zpr, // 4
+        Rel, Iny, Zpi, Imp, Zpx, Zpx, Zpx, Zpg, Imp, Aby, Imp, Imp, Abs, Abx, Abx, Zpr, // 5
+        Imp, Inx, Imm, Imp, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Acc, Imp, Ind, Abs, Abs, Zpr, // 6
+        Rel, Iny, Zpi, Imp, Zpx, Zpx, Zpx, Zpg, Imp, Aby, Imp, Imp, Iax, Abx, Abx, Zpr, // 7
+        Rel, Inx, Imm, Imp, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Imp, Imp, Abs, Abs, Abs, Zpr, // 8
+        Rel, Iny, Zpi, Imp, Zpx, Zpx, Zpy, Zpg, Imp, Aby, Imp, Imp, Abs, Abx, Abx, Zpr, // 9
+        Imm, Inx, Imm, Imp, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Imp, Imp, Abs, Abs, Abs, Zpr, // a
+        Rel, Iny, Zpi, Imp, Zpx, Zpx, Zpy, Zpg, Imp, Aby, Imp, Imp, Abx, Abx, Aby, Zpr, // b
+        Imm, Inx, Imm, Imp, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Imp, Imp, Abs, Abs, Abs, Zpr, // c
+        Rel, Iny, Zpi, Imp, Zpx, Zpx, Zpx, Zpg, Imp, Aby, Imp, Imp, Abs, Abx, Abx, Zpr, // d
+        Imm, Inx, Imm, Imp, Zpg, Zpg, Zpg, Zpg, Imp, Imm, Imp, Imp, Abs, Abs, Abs, Zpr, // e
+        Rel, Iny, Zpi, Imp, Zpx, Zpx, Zpx, Zpg, Imp, Aby, Imp, Imp, Abs, Abx, Abx, Zpr, // f
+    ]
+};
+
+// Zips the grids above into a decode table
+const fn buildDecodeTable(
+    mnemonics: &[&'static str; 256],
+    modes: &[AddressingMode; 256],
+    cycles: &[u8; 256],
+    extracycles: &[u8; 256],
+) -> [OpcodeInfo; 256] {
+    let mut table = [OpcodeInfo {
+        mnemonic: "",
+        mode: AddressingMode::Imp,
+        length: 1,
+        cycles: 0,
+        pagePenalty: 0,
+    }; 256];
+    let mut opcode = 0;
+    while opcode < 256 {
+        table[opcode] = OpcodeInfo {
+            mnemonic: mnemonics[opcode],
+            mode: modes[opcode],
+            length: modes[opcode].length(),
+            cycles: cycles[opcode],
+            pagePenalty: extracycles[opcode],
+        };
+        opcode += 1;
+    }
+    table
+}
+
+// The Rockwell parts lack WAI and STP and the 65SC02 the bit instructions
+// as well. Their slots run as 1 cycle NOPs.
+const fn withoutWdcOpcodes(mut table: [OpcodeInfo; 256], bitInstructions: bool) -> [OpcodeInfo; 256] {
+    let mut opcode = 0;
+    while opcode < 256 {
+        if opcode == 0xcb || opcode == 0xdb || (!bitInstructions && (opcode & 0x07) == 0x07) {
+            table[opcode] = OpcodeInfo {
+                mnemonic: "NOP",
+                mode: AddressingMode::Imp,
+                length: 1,
+                cycles: 1,
+                pagePenalty: 0,
+            };
+        }
+        opcode += 1;
+    }
+    table
+}
+
+pub(crate) static NMOS_DECODE: [OpcodeInfo; 256] =
+    buildDecodeTable(&NMOS_MNEMONICS, &NMOS_MODES, &NMOS_CYCLES, &NMOS_EXTRACYCLES);
+pub(crate) static WDC_DECODE: [OpcodeInfo; 256] =
+    buildDecodeTable(&CMOS_MNEMONICS, &CMOS_MODES, &CMOS_CYCLES, &CMOS_EXTRACYCLES);
+pub(crate) static ROCKWELL_DECODE: [OpcodeInfo; 256] = withoutWdcOpcodes(WDC_DECODE, true);
+pub(crate) static SC02_DECODE: [OpcodeInfo; 256] = withoutWdcOpcodes(WDC_DECODE, false);
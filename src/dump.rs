@@ -0,0 +1,106 @@
+//! Memory dumps split by region, named like the files the firmware build
+//! leaves in dump/. backend/run.sh saves zero.bin, vram.bin, ram.bin and
+//! final.bin from py65 and the Makefile cuts rom.bin and vectors.bin out of
+//! the image, so tools like the VRAM dump viewer can read our runs the same
+//! way.
+
+use std::borrow::Cow;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::bus::Bus;
+use crate::ld65::MemoryArea;
+
+/// A file holding memory from start to end, end included like py65's save
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DumpRegion {
+    pub file: Cow<'static, str>,
+    pub start: u16,
+    pub end: u16,
+}
+
+// where the NMI, reset and IRQ vectors start
+const VECTORS_START: u16 = 0xfffa;
+
+/// The dump directory of a mapache64 game, from the ZEROPAGE, RAM, VRAM and
+/// ROM areas of its map. Like run.sh, ram.bin runs from the zero page to
+/// the end of RAM, so zero.bin is a part of it, and vectors.bin is the end
+/// of ROM. A file is left out when its area is missing.
+pub fn mapache64Dumps(areas: &[MemoryArea]) -> Vec<DumpRegion> {
+    let area = |name: &str| areas.iter().find(|area| area.name == name && area.size > 0);
+    let end = |area: &MemoryArea| (area.start as u32 + area.size - 1) as u16;
+    let region = |file: &'static str, start: u16, end: u16| DumpRegion { file: Cow::Borrowed(file), start, end };
+    let mut regions = Vec::new();
+    if let Some(zero) = area("ZEROPAGE") {
+        regions.push(region("zero.bin", zero.start, end(zero)));
+    }
+    if let Some(ram) = area("RAM") {
+        let start = area("ZEROPAGE").map_or(ram.start, |zero| zero.start.min(ram.start));
+        regions.push(region("ram.bin", start, end(ram)));
+    }
+    if let Some(vram) = area("VRAM") {
+        regions.push(region("vram.bin", vram.start, end(vram)));
+    }
+    if let Some(rom) = area("ROM") {
+        regions.push(region("rom.bin", rom.start, end(rom)));
+        if rom.start <= VECTORS_START && end(rom) == 0xffff {
+            regions.push(region("vectors.bin", VECTORS_START, 0xffff));
+        }
+    }
+    regions.push(region("final.bin", 0x0000, 0xffff));
+    regions
+}
+
+/// A file for each MEMORY area of a linker config, named after the area
+/// like ram.bin, then final.bin with all of memory
+pub fn areaDumps(areas: &[MemoryArea]) -> Vec<DumpRegion> {
+    let mut regions: Vec<_> = areas
+        .iter()
+        .filter(|area| area.size > 0)
+        .map(|area| DumpRegion {
+            file: Cow::Owned(format!("{}.bin", area.name.to_ascii_lowercase())),
+            start: area.start,
+            end: (area.start as u32 + area.size - 1) as u16,
+        })
+        .collect();
+    regions.push(DumpRegion { file: Cow::Borrowed("final.bin"), start: 0x0000, end: 0xffff });
+    regions
+}
+
+/// Memory from start to end, read with peek()
+pub fn dumpRegion<B: Bus>(bus: &B, start: u16, end: u16) -> Vec<u8> {
+    (start..=end).map(|addr| bus.peek(addr)).collect()
+}
+
+/// Write each region to its file in dir, making dir if it isn't there
+pub fn writeDumps<B: Bus>(bus: &B, dir: &Path, regions: &[DumpRegion]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for region in regions {
+        fs::write(dir.join(&*region.file), dumpRegion(bus, region.start, region.end))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::mapache64Areas;
+
+    #[test]
+    fn mapache64DumpRegions() {
+        let regions: Vec<_> = mapache64Dumps(&mapache64Areas())
+            .into_iter()
+            .map(|region| (region.file.into_owned(), region.start, region.end))
+            .collect();
+        let expected = [
+            ("zero.bin", 0x0000, 0x00ff),
+            ("ram.bin", 0x0000, 0x3fff),
+            ("vram.bin", 0x4000, 0x4fff),
+            ("rom.bin", 0x8000, 0xffff),
+            ("vectors.bin", 0xfffa, 0xffff),
+            ("final.bin", 0x0000, 0xffff),
+        ];
+        assert_eq!(regions, expected.map(|(file, start, end)| (file.to_string(), start, end)));
+    }
+}
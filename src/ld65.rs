@@ -0,0 +1,479 @@
+//! Reading memory maps out of cc65 linker configs (ld65 .cfg files), so the
+//! emulator uses the same layout the game was linked for.
+//! See: <https://cc65.github.io/doc/ld65.html#s5>
+
+use std::fmt;
+
+use crate::bus::RegionKind;
+
+/// A linker config that doesn't parse, with the line it went wrong on
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "linker config line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// One area of the MEMORY section
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryArea {
+    pub name: String,
+    pub start: u16,
+    pub size: u32,
+    pub kind: RegionKind,
+    /// the file the linker writes the area to, None for file = ""
+    pub file: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ConfigToken {
+    Ident(String),
+    Number(i64),
+    Str(String),
+    Punct(char),
+}
+
+// name: attribute = value, attribute = value; with the value left as
+// tokens until something asks for it
+struct ConfigEntry {
+    name: String,
+    line: usize,
+    attributes: Vec<(String, Vec<ConfigToken>)>,
+}
+
+impl ConfigEntry {
+    fn attribute(&self, name: &str) -> Option<&Vec<ConfigToken>> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    // the value of an attribute that is a single name or string
+    fn word(&self, name: &str) -> Option<String> {
+        match self.attribute(name)?.as_slice() {
+            [ConfigToken::Ident(word)] | [ConfigToken::Str(word)] => Some(word.clone()),
+            _ => None,
+        }
+    }
+}
+
+fn tokenizeConfig(text: &str) -> Result<Vec<(ConfigToken, usize)>, ConfigError> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = text.chars().collect();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '"' {
+            let start = i + 1;
+            i = start;
+            while i < chars.len() && chars[i] != '"' {
+                if chars[i] == '\n' {
+                    return Err(ConfigError {
+                        line,
+                        message: "unterminated string".to_string(),
+                    });
+                }
+                i += 1;
+            }
+            if i == chars.len() {
+                return Err(ConfigError {
+                    line,
+                    message: "unterminated string".to_string(),
+                });
+            }
+            tokens.push((ConfigToken::Str(chars[start..i].iter().collect()), line));
+            i += 1;
+        } else if c == '$' || c == '%' || c.is_ascii_digit() {
+            // $ is hex and % binary, but %O and friends are file names
+            let radix = match c {
+                '$' => 16,
+                '%' => 2,
+                _ => 10,
+            };
+            let start = if c.is_ascii_digit() { i } else { i + 1 };
+            let mut end = start;
+            while end < chars.len() && (chars[end].is_ascii_alphanumeric() || chars[end] == '_') {
+                end += 1;
+            }
+            let digits: String = chars[start..end].iter().collect();
+            match i64::from_str_radix(&digits, radix) {
+                Ok(number) => tokens.push((ConfigToken::Number(number), line)),
+                Err(_) if c == '%' => {
+                    tokens.push((ConfigToken::Ident(format!("%{}", digits)), line))
+                }
+                Err(_) => {
+                    let text: String = chars[i..end].iter().collect();
+                    return Err(ConfigError {
+                        line,
+                        message: format!("bad number {}", text),
+                    });
+                }
+            }
+            i = end;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((ConfigToken::Ident(chars[start..i].iter().collect()), line));
+        } else if "{}:=,;+-*/()".contains(c) {
+            tokens.push((ConfigToken::Punct(c), line));
+            i += 1;
+        } else {
+            return Err(ConfigError {
+                line,
+                message: format!("unexpected {:?}", c),
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+// Splits the config into its sections, each a list of entries
+fn parseConfigSections(text: &str) -> Result<Vec<(String, Vec<ConfigEntry>)>, ConfigError> {
+    let tokens = tokenizeConfig(text)?;
+    let mut sections = Vec::new();
+    let mut i = 0;
+    let lastLine = tokens.last().map_or(1, |(_, line)| *line);
+    let expected = |i: usize, what: &str| ConfigError {
+        line: tokens.get(i).map_or(lastLine, |(_, line)| *line),
+        message: format!("expected {}", what),
+    };
+
+    while i < tokens.len() {
+        let section = match &tokens[i].0 {
+            ConfigToken::Ident(name) => name.to_ascii_uppercase(),
+            _ => return Err(expected(i, "a section name")),
+        };
+        if tokens.get(i + 1).map(|(token, _)| token) != Some(&ConfigToken::Punct('{')) {
+            return Err(expected(i + 1, "{"));
+        }
+        i += 2;
+
+        let mut entries = Vec::new();
+        loop {
+            let (name, line) = match tokens.get(i) {
+                Some((ConfigToken::Punct('}'), _)) => break,
+                Some((ConfigToken::Ident(name), line)) => (name.clone(), *line),
+                _ => return Err(expected(i, "an entry name or }")),
+            };
+            if tokens.get(i + 1).map(|(token, _)| token) != Some(&ConfigToken::Punct(':')) {
+                return Err(expected(i + 1, ":"));
+            }
+            i += 2;
+
+            let mut attributes = Vec::new();
+            loop {
+                let attribute = match tokens.get(i) {
+                    Some((ConfigToken::Ident(attribute), _)) => attribute.clone(),
+                    _ => return Err(expected(i, "an attribute name")),
+                };
+                if tokens.get(i + 1).map(|(token, _)| token) != Some(&ConfigToken::Punct('=')) {
+                    return Err(expected(i + 1, "="));
+                }
+                i += 2;
+                // the value runs to a comma, a semicolon or the next
+                // attribute, the commas between attributes are optional
+                let mut value = Vec::new();
+                while let Some((token, _)) = tokens.get(i) {
+                    let nextIsEquals =
+                        tokens.get(i + 1).map(|(token, _)| token) == Some(&ConfigToken::Punct('='));
+                    match token {
+                        ConfigToken::Punct(',') | ConfigToken::Punct(';') => break,
+                        ConfigToken::Ident(_) if nextIsEquals && !value.is_empty() => break,
+                        _ => value.push(token.clone()),
+                    }
+                    i += 1;
+                }
+                attributes.push((attribute, value));
+                if tokens.get(i).map(|(token, _)| token) == Some(&ConfigToken::Punct(',')) {
+                    i += 1;
+                }
+                match tokens.get(i) {
+                    Some((ConfigToken::Punct(';'), _)) => {
+                        i += 1;
+                        break;
+                    }
+                    Some((ConfigToken::Ident(_), _)) => {}
+                    _ => return Err(expected(i, ", or ;")),
+                }
+            }
+            entries.push(ConfigEntry {
+                name,
+                line,
+                attributes,
+            });
+        }
+        i += 1;
+        sections.push((section, entries));
+    }
+    Ok(sections)
+}
+
+// Evaluates a start, size or symbol value: numbers and SYMBOLS names with
+// + - * / and parentheses
+struct ConfigExpression<'a> {
+    tokens: &'a [ConfigToken],
+    position: usize,
+    symbols: &'a [&'a ConfigEntry],
+    line: usize,
+    depth: usize,
+}
+
+impl ConfigExpression<'_> {
+    fn error(&self, message: String) -> ConfigError {
+        ConfigError {
+            line: self.line,
+            message,
+        }
+    }
+
+    // the result of checked arithmetic, which is None on overflow
+    fn checked(&self, value: Option<i64>) -> Result<i64, ConfigError> {
+        value.ok_or_else(|| self.error("number too big".to_string()))
+    }
+
+    fn evaluate(
+        tokens: &[ConfigToken],
+        symbols: &[&ConfigEntry],
+        line: usize,
+        depth: usize,
+    ) -> Result<i64, ConfigError> {
+        let mut expression = ConfigExpression {
+            tokens,
+            position: 0,
+            symbols,
+            line,
+            depth,
+        };
+        if depth > symbols.len() {
+            return Err(expression.error("symbols refer to each other in a loop".to_string()));
+        }
+        let value = expression.sum()?;
+        if expression.position != tokens.len() {
+            return Err(expression.error("bad expression".to_string()));
+        }
+        Ok(value)
+    }
+
+    fn sum(&mut self) -> Result<i64, ConfigError> {
+        let mut value = self.product()?;
+        loop {
+            match self.tokens.get(self.position) {
+                Some(ConfigToken::Punct('+')) => {
+                    self.position += 1;
+                    let operand = self.product()?;
+                    value = self.checked(value.checked_add(operand))?;
+                }
+                Some(ConfigToken::Punct('-')) => {
+                    self.position += 1;
+                    let operand = self.product()?;
+                    value = self.checked(value.checked_sub(operand))?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<i64, ConfigError> {
+        let mut value = self.operand()?;
+        loop {
+            match self.tokens.get(self.position) {
+                Some(ConfigToken::Punct('*')) => {
+                    self.position += 1;
+                    let operand = self.operand()?;
+                    value = self.checked(value.checked_mul(operand))?;
+                }
+                Some(ConfigToken::Punct('/')) => {
+                    self.position += 1;
+                    let divisor = self.operand()?;
+                    if divisor == 0 {
+                        return Err(self.error("division by zero".to_string()));
+                    }
+                    value = self.checked(value.checked_div(divisor))?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    fn operand(&mut self) -> Result<i64, ConfigError> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        match token {
+            Some(ConfigToken::Number(number)) => Ok(number),
+            Some(ConfigToken::Punct('-')) => {
+                let value = self.operand()?;
+                self.checked(value.checked_neg())
+            }
+            Some(ConfigToken::Punct('(')) => {
+                let value = self.sum()?;
+                if self.tokens.get(self.position) != Some(&ConfigToken::Punct(')')) {
+                    return Err(self.error("expected )".to_string()));
+                }
+                self.position += 1;
+                Ok(value)
+            }
+            Some(ConfigToken::Ident(name)) => {
+                let symbol = self.symbols.iter().find(|symbol| symbol.name == name);
+                match symbol
+                    .and_then(|symbol| symbol.attribute("value").map(|value| (symbol, value)))
+                {
+                    Some((symbol, value)) => {
+                        ConfigExpression::evaluate(value, self.symbols, symbol.line, self.depth + 1)
+                    }
+                    None => Err(self.error(format!("unknown symbol {}", name))),
+                }
+            }
+            _ => Err(self.error("bad expression".to_string())),
+        }
+    }
+}
+
+/// The MEMORY areas of an ld65 config. An area's kind comes from its type
+/// attribute if it has one. Otherwise it is RAM when a writable segment
+/// (rw, bss or zp) runs in it and ROM when only read-only ones do. An area
+/// without segments is RAM if it isn't written to a file (file = "").
+pub fn parseLinkerConfig(text: &str) -> Result<Vec<MemoryArea>, ConfigError> {
+    let sections = parseConfigSections(text)?;
+    let entries = |name: &str| {
+        sections
+            .iter()
+            .filter(|(section, _)| section == name)
+            .flat_map(|(_, entries)| entries.iter())
+            .collect::<Vec<_>>()
+    };
+    let symbols = entries("SYMBOLS");
+    let segments = entries("SEGMENTS");
+
+    let mut areas = Vec::new();
+    for area in entries("MEMORY") {
+        let number = |attribute: &str| match area.attribute(attribute) {
+            Some(value) => ConfigExpression::evaluate(value, &symbols, area.line, 0),
+            None => Err(ConfigError {
+                line: area.line,
+                message: format!("{} has no {}", area.name, attribute),
+            }),
+        };
+        let start = number("start")?;
+        let size = number("size")?;
+        if !(0..=0xffff).contains(&start) || !(0..=0x10000 - start).contains(&size) {
+            return Err(ConfigError {
+                line: area.line,
+                message: format!("{} doesn't fit in 64K", area.name),
+            });
+        }
+
+        // file defaults to the linker's output file, %O
+        let file = match area.word("file") {
+            Some(file) if file.is_empty() => None,
+            Some(file) => Some(file),
+            None => Some("%O".to_string()),
+        };
+
+        let mut writable = None;
+        for segment in &segments {
+            let runsIn = segment.word("run").or_else(|| segment.word("load"));
+            if runsIn.as_deref() != Some(area.name.as_str()) {
+                continue;
+            }
+            let segmentWritable = matches!(
+                segment.word("type").as_deref(),
+                Some("rw") | Some("bss") | Some("zp")
+            );
+            writable = Some(writable.unwrap_or(false) || segmentWritable);
+        }
+        let writable = match area.word("type").as_deref() {
+            Some("ro") => false,
+            Some("rw") => true,
+            Some(other) => {
+                return Err(ConfigError {
+                    line: area.line,
+                    message: format!("unknown type {}", other),
+                });
+            }
+            None => writable.unwrap_or(file.is_none()),
+        };
+
+        areas.push(MemoryArea {
+            name: area.name.clone(),
+            start: start as u16,
+            size: size as u32,
+            kind: if writable {
+                RegionKind::Ram
+            } else {
+                RegionKind::Rom
+            },
+            file,
+        });
+    }
+    Ok(areas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(name: &str, start: u16, size: u32, kind: RegionKind, file: Option<&str>) -> MemoryArea {
+        MemoryArea {
+            name: name.to_string(),
+            start,
+            size,
+            kind,
+            file: file.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn mapache64Config() {
+        let text = include_str!("../benchmark1/template/backend/mapache64.cfg");
+        assert_eq!(
+            parseLinkerConfig(text).unwrap(),
+            [
+                area("ZEROPAGE", 0x0000, 0x0100, RegionKind::Ram, None),
+                area("RAM", 0x0200, 0x3e00, RegionKind::Ram, None),
+                area("VRAM", 0x4000, 0x1000, RegionKind::Ram, None),
+                area("FIRMWARE", 0x5000, 0x2000, RegionKind::Rom, Some("%O_fw.bin")),
+                area("IO", 0x7000, 0x1000, RegionKind::Ram, Some("%O_io.bin")),
+                area("ROM", 0x8000, 0x8000, RegionKind::Rom, Some("%O_rom.bin")),
+            ]
+        );
+    }
+
+    #[test]
+    fn symbolExpressions() {
+        let text = "SYMBOLS { __STACKSIZE__: type = weak, value = $0800; }
+            MEMORY { MAIN: start = $1000, size = $d000 - $1000 - __STACKSIZE__ * 2, type = rw; }";
+        assert_eq!(
+            parseLinkerConfig(text).unwrap(),
+            [area("MAIN", 0x1000, 0xb000, RegionKind::Ram, Some("%O"))]
+        );
+    }
+
+    #[test]
+    fn overflowIsAnError() {
+        let error = parseLinkerConfig("MEMORY {\n A: start = 1, size = $7fffffffffffffff * 2; }").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "number too big");
+        let error = parseLinkerConfig("MEMORY { A: start = 1, size = $7fffffffffffffff; }").unwrap_err();
+        assert_eq!(error.message, "A doesn't fit in 64K");
+    }
+}
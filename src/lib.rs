@@ -0,0 +1,113 @@
+//! A 6502 emulator ported from py65, with the NMOS 6502 and the WDC,
+//! Rockwell and 65SC02 flavors of the 65C02. It makes every bus access the
+//! chip does, one per cycle, so it can run mapache64 games against their
+//! memory-mapped devices.
+//!
+//! Mpu6502 is the CPU. It runs on anything implementing Bus: FlatRam is 64K
+//! of plain memory and MemoryMap splits the address space into RAM, ROM and
+//! unmapped regions, optionally read from an ld65 linker config.
+//!
+//! ```no_run
+//! use emulator_6502::{CpuVariant, MemoryMap, Mpu6502, RunState};
+//!
+//! let mut cpu = Mpu6502::withBus(CpuVariant::Wdc65C02, MemoryMap::mapache64());
+//! let image = std::fs::read("mapache64.bin").unwrap();
+//! cpu.busMut().load(0, &image);
+//! cpu.reset();
+//! while cpu.runState() != RunState::Stopped {
+//!     cpu.step().unwrap();
+//! }
+//! println!("{} cycles", cpu.processorCycles());
+//! ```
+
+#![allow(non_snake_case)]
+
+mod bus;
+mod cpu;
+mod decode;
+mod dump;
+mod ld65;
+mod loader;
+mod mapache64;
+mod savestate;
+mod video;
+mod vram;
+
+pub use bus::mapache64Areas;
+pub use bus::Bus;
+pub use bus::BusFault;
+pub use bus::FlatRam;
+pub use bus::MemoryMap;
+pub use bus::RegionKind;
+pub use cpu::CpuVariant;
+pub use cpu::Mpu6502;
+pub use cpu::Registers;
+pub use cpu::RunState;
+pub use cpu::Status;
+pub use cpu::StepError;
+pub use cpu::UndefinedOpcodePolicy;
+pub use decode::AddressingMode;
+pub use decode::OpcodeInfo;
+pub use dump::areaDumps;
+pub use dump::dumpRegion;
+pub use dump::mapache64Dumps;
+pub use dump::writeDumps;
+pub use dump::DumpRegion;
+pub use ld65::parseLinkerConfig;
+pub use ld65::ConfigError;
+pub use ld65::MemoryArea;
+pub use loader::loadImage;
+pub use loader::parseIntelHex;
+pub use loader::parseO65;
+pub use loader::parseRaw;
+pub use loader::parseSRecord;
+pub use loader::Image;
+pub use loader::ImageFormat;
+pub use loader::LoadError;
+pub use loader::Segment;
+pub use mapache64::runFrame;
+pub use mapache64::syncVblank;
+pub use mapache64::Buttons;
+pub use mapache64::Controller;
+pub use mapache64::InputError;
+pub use mapache64::InputScript;
+pub use mapache64::Mapache64Bus;
+pub use mapache64::CLR_VBLANK_IRQ;
+pub use mapache64::CONTROLLER_1;
+pub use mapache64::CONTROLLER_2;
+pub use mapache64::DEFAULT_CLOCK_HZ;
+pub use mapache64::FRAMES_PER_SECOND;
+pub use mapache64::IN_VBLANK;
+pub use savestate::SaveStateError;
+pub use savestate::StateReader;
+pub use savestate::StateWriter;
+pub use savestate::SAVE_STATE_MAGIC;
+pub use savestate::SAVE_STATE_VERSION;
+pub use video::renderScreen;
+pub use video::renderVram;
+pub use video::Frame;
+pub use video::SCREEN_HEIGHT;
+pub use video::SCREEN_WIDTH;
+pub use vram::BackgroundPalette;
+pub use vram::CharTile;
+pub use vram::Object;
+pub use vram::Pattern;
+pub use vram::Tile;
+pub use vram::Vram;
+pub use vram::BACKGROUND_PALETTE;
+pub use vram::CHAR_VALUE_MASK;
+pub use vram::COLOR_SELECT_MASK;
+pub use vram::HFLIP_MASK;
+pub use vram::NTBL_START;
+pub use vram::OBJECT_COUNT;
+pub use vram::OBM_START;
+pub use vram::PATTERN_ADDRESS_MASK;
+pub use vram::PATTERN_COUNT;
+pub use vram::PMB_START;
+pub use vram::PMF_START;
+pub use vram::TABLE_COLUMNS;
+pub use vram::TABLE_ROWS;
+pub use vram::TXBL_START;
+pub use vram::VFLIP_MASK;
+pub use vram::VRAM_SIZE;
+pub use vram::VRAM_START;
@@ -0,0 +1,535 @@
+//! Readers for the image formats we load into memory: raw binaries at an
+//! address, Intel HEX, Motorola S-records and the o65 relocatable format
+//! from xa and cc65. Each one turns a file into an Image, a list of
+//! segments to copy into the 64K address space, and checks that every byte
+//! lands inside it.
+
+use std::fmt;
+
+/// Why a file couldn't be read as an image
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoadError {
+    /// the line of a text format the problem is on
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl LoadError {
+    fn new(message: impl Into<String>) -> Self {
+        LoadError {
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    fn onLine(line: usize, message: impl Into<String>) -> Self {
+        LoadError {
+            line: Some(line),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// The formats loadImage knows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Raw,
+    IntelHex,
+    SRecord,
+    O65,
+}
+
+impl ImageFormat {
+    /// Guess from the file extension, anything unknown is raw
+    pub fn fromPath(path: &str) -> Self {
+        let extension = match path.rsplit_once('.') {
+            Some((_, extension)) => extension.to_ascii_lowercase(),
+            None => String::new(),
+        };
+        match extension.as_str() {
+            "hex" | "ihx" | "ihex" => ImageFormat::IntelHex,
+            "srec" | "s19" | "s28" | "s37" | "mot" => ImageFormat::SRecord,
+            "o65" => ImageFormat::O65,
+            _ => ImageFormat::Raw,
+        }
+    }
+
+    /// raw, ihex, srec or o65
+    pub fn fromName(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "raw" | "bin" => Some(ImageFormat::Raw),
+            "ihex" | "hex" => Some(ImageFormat::IntelHex),
+            "srec" => Some(ImageFormat::SRecord),
+            "o65" => Some(ImageFormat::O65),
+            _ => None,
+        }
+    }
+}
+
+/// Bytes that go at start
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub start: u16,
+    pub bytes: Vec<u8>,
+}
+
+/// What a loader read: the segments to copy into memory, in order, and the
+/// start address the file gives if it has one
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Image {
+    pub segments: Vec<Segment>,
+    pub entry: Option<u16>,
+}
+
+impl Image {
+    /// Copy the segments into memory, later ones win where they overlap.
+    /// Every bus we have keeps its contents in a 64K array.
+    pub fn copyInto(&self, memory: &mut [u8; 0x10000]) {
+        for segment in &self.segments {
+            let start = segment.start as usize;
+            memory[start..start + segment.bytes.len()].copy_from_slice(&segment.bytes);
+        }
+    }
+
+    // adds bytes at address, running on from the last segment when they
+    // continue it so records don't each become a segment
+    fn add(&mut self, address: u32, bytes: &[u8]) -> Result<(), String> {
+        if address as usize + bytes.len() > 0x10000 {
+            return Err(format!(
+                "{} bytes at {:#06x} run past the end of memory",
+                bytes.len(),
+                address
+            ));
+        }
+        if let Some(last) = self.segments.last_mut() {
+            if last.start as usize + last.bytes.len() == address as usize {
+                last.bytes.extend_from_slice(bytes);
+                return Ok(());
+            }
+        }
+        self.segments.push(Segment {
+            start: address as u16,
+            bytes: bytes.to_vec(),
+        });
+        Ok(())
+    }
+}
+
+/// Read file as format. start is where a raw image goes and where an o65
+/// text segment is relocated to, None leaves o65 where it was linked.
+pub fn loadImage(format: ImageFormat, file: &[u8], start: Option<u16>) -> Result<Image, LoadError> {
+    match format {
+        ImageFormat::Raw => parseRaw(file, start.unwrap_or(0)),
+        ImageFormat::IntelHex => parseIntelHex(asText(file)?),
+        ImageFormat::SRecord => parseSRecord(asText(file)?),
+        ImageFormat::O65 => parseO65(file, start),
+    }
+}
+
+fn asText(file: &[u8]) -> Result<&str, LoadError> {
+    std::str::from_utf8(file).map_err(|_| LoadError::new("isn't a text file"))
+}
+
+/// The whole file at start
+pub fn parseRaw(file: &[u8], start: u16) -> Result<Image, LoadError> {
+    let mut image = Image::default();
+    image.add(start as u32, file).map_err(LoadError::new)?;
+    Ok(image)
+}
+
+// The bytes of a record written as hex digits
+fn hexBytes(digits: &str, line: usize) -> Result<Vec<u8>, LoadError> {
+    if !digits.len().is_multiple_of(2) || !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return Err(LoadError::onLine(line, "record isn't pairs of hex digits"));
+    }
+    Ok((0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap())
+        .collect())
+}
+
+/// Intel HEX: data, end of file, extended segment and linear address and
+/// the two start address records. Every checksum is checked.
+pub fn parseIntelHex(text: &str) -> Result<Image, LoadError> {
+    let mut image = Image::default();
+    // added to record addresses by the extended address records
+    let mut base: u32 = 0;
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let digits = line
+            .strip_prefix(':')
+            .ok_or_else(|| LoadError::onLine(number, "record doesn't start with :"))?;
+        let record = hexBytes(digits, number)?;
+        if record.len() < 5 || record.len() != record[0] as usize + 5 {
+            return Err(LoadError::onLine(number, "record length doesn't match its count"));
+        }
+        if record.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0 {
+            return Err(LoadError::onLine(number, "bad checksum"));
+        }
+        let address = u16::from_be_bytes([record[1], record[2]]) as u32;
+        let data = &record[4..record.len() - 1];
+        match record[3] {
+            0x00 => image
+                .add(base + address, data)
+                .map_err(|message| LoadError::onLine(number, message))?,
+            0x01 => return Ok(image),
+            0x02 | 0x04 if data.len() == 2 => {
+                let value = u16::from_be_bytes([data[0], data[1]]) as u32;
+                base = if record[3] == 0x02 { value << 4 } else { value << 16 };
+            }
+            0x03 | 0x05 if data.len() == 4 => {
+                let entry = match record[3] {
+                    0x03 => {
+                        let segment = u16::from_be_bytes([data[0], data[1]]) as u32;
+                        (segment << 4) + u16::from_be_bytes([data[2], data[3]]) as u32
+                    }
+                    _ => u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+                };
+                let entry = u16::try_from(entry).map_err(|_| {
+                    LoadError::onLine(number, format!("start address {:#x} is past 0xffff", entry))
+                })?;
+                image.entry = Some(entry);
+            }
+            0x02..=0x05 => return Err(LoadError::onLine(number, "record has the wrong length")),
+            kind => {
+                return Err(LoadError::onLine(number, format!("unknown record type {:02x}", kind)))
+            }
+        }
+    }
+    Err(LoadError::new("no end of file record"))
+}
+
+/// Motorola S-records: S1, S2 and S3 data, S0 headers and S5/S6 counts
+/// (skipped), and S7, S8 or S9 start addresses. cc65 writes S9 with
+/// address 0 when there is no start address, so 0 counts as none.
+pub fn parseSRecord(text: &str) -> Result<Image, LoadError> {
+    let mut image = Image::default();
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut chars = line.chars();
+        if chars.next() != Some('S') {
+            return Err(LoadError::onLine(number, "record doesn't start with S"));
+        }
+        let kind = chars.next().and_then(|c| c.to_digit(10));
+        let record = hexBytes(chars.as_str(), number)?;
+        if record.is_empty() || record.len() != record[0] as usize + 1 {
+            return Err(LoadError::onLine(number, "record length doesn't match its count"));
+        }
+        if record.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0xff {
+            return Err(LoadError::onLine(number, "bad checksum"));
+        }
+        let addressLength = match kind {
+            Some(0 | 1 | 5 | 9) => 2,
+            Some(2 | 6 | 8) => 3,
+            Some(3 | 7) => 4,
+            _ => return Err(LoadError::onLine(number, "unknown record type")),
+        };
+        let body = &record[1..record.len() - 1];
+        if body.len() < addressLength {
+            return Err(LoadError::onLine(number, "record is too short for its address"));
+        }
+        let address = body[..addressLength]
+            .iter()
+            .fold(0u32, |address, &b| (address << 8) | b as u32);
+        let data = &body[addressLength..];
+        match kind {
+            Some(1..=3) => image
+                .add(address, data)
+                .map_err(|message| LoadError::onLine(number, message))?,
+            Some(7..=9) => {
+                let entry = u16::try_from(address).map_err(|_| {
+                    LoadError::onLine(number, format!("start address {:#x} is past 0xffff", address))
+                })?;
+                image.entry = if entry == 0 { None } else { Some(entry) };
+            }
+            _ => {}
+        }
+    }
+    Ok(image)
+}
+
+// o65 mode bits
+const O65_65816: u16 = 0x8000;
+const O65_PAGED: u16 = 0x4000;
+const O65_32BIT: u16 = 0x2000;
+const O65_CHAIN: u16 = 0x0400;
+const O65_BSSZERO: u16 = 0x0200;
+
+// Walks an o65 file, running off the end is an error
+struct O65Reader<'a> {
+    file: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> O65Reader<'a> {
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], LoadError> {
+        if self.offset + count > self.file.len() {
+            return Err(LoadError::new(format!("o65 file ends too soon at {}", self.file.len())));
+        }
+        let bytes = &self.file[self.offset..self.offset + count];
+        self.offset += count;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, LoadError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn word(&mut self) -> Result<u16, LoadError> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    // a null terminated name
+    fn name(&mut self) -> Result<String, LoadError> {
+        let length = self.file[self.offset..]
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| LoadError::new("o65 name isn't terminated"))?;
+        let name = String::from_utf8_lossy(&self.file[self.offset..self.offset + length]);
+        self.offset += length + 1;
+        Ok(name.into_owned())
+    }
+}
+
+/// o65 with 16 bit addresses, as xa and ld65 write it. The text segment is
+/// relocated to start (where it was linked when None), data follows it and
+/// bss follows data, zero page stays where it was linked. Files that import
+/// symbols can't be loaded since there is nothing to resolve them against.
+pub fn parseO65(file: &[u8], start: Option<u16>) -> Result<Image, LoadError> {
+    let mut reader = O65Reader { file, offset: 0 };
+    if reader.bytes(6)? != [0x01, 0x00, b'o', b'6', b'5', 0x00] {
+        return Err(LoadError::new("not an o65 file"));
+    }
+    let mode = reader.word()?;
+    if mode & O65_32BIT != 0 {
+        return Err(LoadError::new("o65 files with 32 bit sizes aren't supported"));
+    }
+    if mode & O65_CHAIN != 0 {
+        return Err(LoadError::new("chained o65 files aren't supported"));
+    }
+    if mode & O65_65816 != 0 {
+        return Err(LoadError::new("65816 o65 files aren't supported"));
+    }
+    let tbase = reader.word()?;
+    let tlen = reader.word()?;
+    let dbase = reader.word()?;
+    let dlen = reader.word()?;
+    let bbase = reader.word()?;
+    let blen = reader.word()?;
+    let _zbase = reader.word()?;
+    let _zlen = reader.word()?;
+    let _stack = reader.word()?;
+    // header options, a length byte that counts itself, 0 ends them
+    loop {
+        let length = reader.byte()?;
+        if length == 0 {
+            break;
+        }
+        if length < 2 {
+            return Err(LoadError::new("o65 header option is too short"));
+        }
+        reader.bytes(length as usize - 1)?;
+    }
+    let mut text = reader.bytes(tlen as usize)?.to_vec();
+    let mut data = reader.bytes(dlen as usize)?.to_vec();
+    let imports = reader.word()?;
+    if imports != 0 {
+        let name = reader.name()?;
+        return Err(LoadError::new(format!(
+            "o65 file imports {} symbols, like {}, which can't be resolved",
+            imports, name
+        )));
+    }
+
+    let textStart = start.unwrap_or(tbase);
+    let dataStart = textStart as u32 + tlen as u32;
+    let bssStart = dataStart + dlen as u32;
+    if bssStart + blen as u32 > 0x10000 {
+        return Err(LoadError::new(format!(
+            "o65 segments at {:#06x} run past the end of memory",
+            textStart
+        )));
+    }
+    // how far each segment id moves, undefined (0) and absolute (1) don't,
+    // and neither does zero page (5)
+    let deltas = [
+        0,
+        0,
+        textStart.wrapping_sub(tbase),
+        (dataStart as u16).wrapping_sub(dbase),
+        (bssStart as u16).wrapping_sub(bbase),
+        0,
+    ];
+    relocateO65(&mut reader, &mut text, tbase, &deltas, mode & O65_PAGED != 0)?;
+    relocateO65(&mut reader, &mut data, dbase, &deltas, mode & O65_PAGED != 0)?;
+
+    let mut image = Image::default();
+    image.add(textStart as u32, &text).map_err(LoadError::new)?;
+    image.add(dataStart, &data).map_err(LoadError::new)?;
+    if mode & O65_BSSZERO != 0 {
+        image.add(bssStart, &vec![0; blen as usize]).map_err(LoadError::new)?;
+    }
+    Ok(image)
+}
+
+// Apply one relocation table to segment, which was linked at base
+fn relocateO65(
+    reader: &mut O65Reader,
+    segment: &mut [u8],
+    base: u16,
+    deltas: &[u16; 6],
+    paged: bool,
+) -> Result<(), LoadError> {
+    // offsets count from the byte before the segment
+    let mut address = base as i64 - 1;
+    loop {
+        let mut offset = reader.byte()?;
+        if offset == 0 {
+            return Ok(());
+        }
+        while offset == 255 {
+            address += 254;
+            offset = reader.byte()?;
+        }
+        address += offset as i64;
+        let kind = reader.byte()?;
+        let segmentId = (kind & 0x0f) as usize;
+        if segmentId == 0 {
+            return Err(LoadError::new("o65 relocation refers to an import"));
+        }
+        let delta = *deltas
+            .get(segmentId)
+            .ok_or_else(|| LoadError::new(format!("o65 relocation to unknown segment {}", segmentId)))?;
+        let at = (address - base as i64) as usize;
+        let size = if kind & 0xf0 == 0x80 { 2 } else { 1 };
+        if at + size > segment.len() {
+            return Err(LoadError::new(format!(
+                "o65 relocation at {:#06x} is outside its segment",
+                address
+            )));
+        }
+        match kind & 0xf0 {
+            0x80 => {
+                let value = u16::from_le_bytes([segment[at], segment[at + 1]]).wrapping_add(delta);
+                segment[at..at + 2].copy_from_slice(&value.to_le_bytes());
+            }
+            0x40 => {
+                // the low byte decides the carry into the high one
+                let low = if paged { 0 } else { reader.byte()? };
+                let value = u16::from_le_bytes([low, segment[at]]).wrapping_add(delta);
+                segment[at] = (value >> 8) as u8;
+            }
+            0x20 => segment[at] = segment[at].wrapping_add(delta as u8),
+            other => {
+                return Err(LoadError::new(format!(
+                    "o65 relocation type {:#04x} isn't supported",
+                    other
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intelHex() {
+        let image = parseIntelHex(":0300300002337A1E\n:00000001FF\n").unwrap();
+        assert_eq!(image.segments, [Segment { start: 0x0030, bytes: vec![0x02, 0x33, 0x7a] }]);
+        assert_eq!(image.entry, None);
+    }
+
+    #[test]
+    fn intelHexBadChecksum() {
+        let error = parseIntelHex(":0300300002337A1F\n:00000001FF\n").unwrap_err();
+        assert_eq!(error, LoadError::onLine(1, "bad checksum"));
+    }
+
+    #[test]
+    fn sRecordBadChecksum() {
+        let error = parseSRecord("S9030000FC\nS1130000285F245F2212226A000424290008237C2B\n").unwrap_err();
+        assert_eq!(error, LoadError::onLine(2, "bad checksum"));
+    }
+
+    #[test]
+    fn sRecordStartAddress() {
+        let text = "S1130000285F245F2212226A000424290008237C2A\nS9031234B6\n";
+        let image = parseSRecord(text).unwrap();
+        assert_eq!(image.segments[0].start, 0x0000);
+        assert_eq!(image.segments[0].bytes.len(), 16);
+        assert_eq!(image.entry, Some(0x1234));
+    }
+
+    #[test]
+    fn sRecordS9AtZeroIsNoStartAddress() {
+        let image = parseSRecord("S1130000285F245F2212226A000424290008237C2A\nS9030000FC\n").unwrap();
+        assert_eq!(image.entry, None);
+    }
+
+    // An o65 file linked at 0x1000 whose text is LDA #<$10ff, LDA #>$10ff,
+    // with LOW and HIGH relocations against the text segment
+    fn o65(paged: bool) -> Vec<u8> {
+        let mode = if paged { O65_PAGED } else { 0 };
+        let mut file = vec![0x01, 0x00, b'o', b'6', b'5', 0x00];
+        // mode, tbase, tlen, dbase, dlen, bbase, blen, zbase, zlen, stack
+        for word in [mode, 0x1000, 4, 0x1004, 0, 0x1004, 0, 0, 0, 0] {
+            file.extend_from_slice(&word.to_le_bytes());
+        }
+        file.push(0);
+        file.extend_from_slice(&[0xa9, 0xff, 0xa9, 0x10]);
+        // no imports
+        file.extend_from_slice(&[0, 0]);
+        // text relocations: LOW at 0x1001, HIGH at 0x1003 with its low byte
+        // unless paged, then the end
+        file.extend_from_slice(&[2, 0x22, 2, 0x42]);
+        if !paged {
+            file.push(0xff);
+        }
+        file.push(0);
+        // no data relocations or exports
+        file.extend_from_slice(&[0, 0, 0]);
+        file
+    }
+
+    #[test]
+    fn o65Relocation() {
+        // 0x10ff moves to 0x217f, the low byte carries into the high one
+        let image = parseO65(&o65(false), Some(0x2080)).unwrap();
+        assert_eq!(image.segments, [Segment { start: 0x2080, bytes: vec![0xa9, 0x7f, 0xa9, 0x21] }]);
+    }
+
+    #[test]
+    fn o65PagedRelocation() {
+        // paged files only move by whole pages, so there's no low byte
+        let image = parseO65(&o65(true), Some(0x2000)).unwrap();
+        assert_eq!(image.segments, [Segment { start: 0x2000, bytes: vec![0xa9, 0xff, 0xa9, 0x20] }]);
+    }
+
+    #[test]
+    fn o65WhereLinked() {
+        let image = parseO65(&o65(false), None).unwrap();
+        assert_eq!(image.segments, [Segment { start: 0x1000, bytes: vec![0xa9, 0xff, 0xa9, 0x10] }]);
+    }
+}
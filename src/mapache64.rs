@@ -0,0 +1,487 @@
+//! The mapache64 board around the CPU: its memory map plus the vblank and
+//! controller registers in the IO area. The firmware's io.s hardcodes
+//! _IN_VBLANK to 1 for py65, which runs a game's frame loop once at most.
+//! Here the GPU's vblank comes around 60 times a second of emulated cycles,
+//! raising IRQ so crt0.s's _handle_irq runs and the game's frame loop keeps
+//! going, and the controllers hold whatever buttons the host presses.
+
+use std::fmt;
+use std::ops::BitAnd;
+use std::ops::BitOr;
+use std::ops::BitOrAssign;
+use std::ops::RangeInclusive;
+
+use crate::bus::Bus;
+use crate::bus::BusFault;
+use crate::bus::MemoryMap;
+use crate::cpu::Mpu6502;
+use crate::cpu::StepError;
+use crate::savestate::SaveStateError;
+use crate::savestate::StateReader;
+use crate::savestate::StateWriter;
+
+/// Reads 1 while the GPU is in vblank and 0 while it draws
+pub const IN_VBLANK: u16 = 0x7000;
+/// Any write acknowledges the vblank IRQ
+pub const CLR_VBLANK_IRQ: u16 = 0x7001;
+/// The buttons held on each controller, read only
+pub const CONTROLLER_1: u16 = 0x7002;
+pub const CONTROLLER_2: u16 = 0x7003;
+
+/// The CPU clock when none is given. The board's isn't written down in the
+/// firmware, so this is a round number.
+pub const DEFAULT_CLOCK_HZ: u64 = 1_000_000;
+pub const FRAMES_PER_SECOND: u64 = 60;
+
+/// The buttons held on a controller, bit for bit as CONTROLLER_1 and
+/// CONTROLLER_2 hold them and with controller.h's masks. They combine with
+/// | and &, like Status flags.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Buttons(u8);
+
+impl Buttons {
+    pub const NONE: Buttons = Buttons(0);
+    pub const A: Buttons = Buttons(0x80);
+    pub const B: Buttons = Buttons(0x40);
+    pub const SELECT: Buttons = Buttons(0x20);
+    pub const START: Buttons = Buttons(0x10);
+    pub const UP: Buttons = Buttons(0x08);
+    pub const DOWN: Buttons = Buttons(0x04);
+    pub const LEFT: Buttons = Buttons(0x02);
+    pub const RIGHT: Buttons = Buttons(0x01);
+
+    pub const fn fromBits(bits: u8) -> Buttons {
+        Buttons(bits)
+    }
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// true if every button in buttons is held
+    pub fn contains(self, buttons: Buttons) -> bool {
+        self.0 & buttons.0 == buttons.0
+    }
+
+    pub fn insert(&mut self, buttons: Buttons) {
+        self.0 |= buttons.0;
+    }
+
+    pub fn remove(&mut self, buttons: Buttons) {
+        self.0 &= !buttons.0;
+    }
+
+    /// Button names joined by +, like `a+start` or `Up+Left`, or - for none
+    pub fn parse(text: &str) -> Option<Buttons> {
+        if text == "-" {
+            return Some(Buttons::NONE);
+        }
+        let mut buttons = Buttons::NONE;
+        for name in text.split('+') {
+            buttons |= match name.to_ascii_lowercase().as_str() {
+                "a" => Buttons::A,
+                "b" => Buttons::B,
+                "select" => Buttons::SELECT,
+                "start" => Buttons::START,
+                "up" => Buttons::UP,
+                "down" => Buttons::DOWN,
+                "left" => Buttons::LEFT,
+                "right" => Buttons::RIGHT,
+                _ => return None,
+            };
+        }
+        Some(buttons)
+    }
+}
+
+impl BitOr for Buttons {
+    type Output = Buttons;
+    fn bitor(self, other: Buttons) -> Buttons {
+        Buttons(self.0 | other.0)
+    }
+}
+
+impl BitAnd for Buttons {
+    type Output = Buttons;
+    fn bitand(self, other: Buttons) -> Buttons {
+        Buttons(self.0 & other.0)
+    }
+}
+
+impl BitOrAssign for Buttons {
+    fn bitor_assign(&mut self, other: Buttons) {
+        self.0 |= other.0;
+    }
+}
+
+/// The two controller ports, CONTROLLER_1 and CONTROLLER_2
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Controller {
+    One,
+    Two,
+}
+
+/// Why an input script couldn't be read
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "input line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// What both controllers hold from one frame on, so a run can press
+/// buttons at the same point every time. Frames are counted as they
+/// finish: changes at frame 0 hold from the start and changes at frame n
+/// once n frames have been drawn.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputScript {
+    // sorted by frame, one change a frame
+    changes: Vec<(u64, [Buttons; 2])>,
+}
+
+impl InputScript {
+    pub fn new() -> Self {
+        InputScript::default()
+    }
+
+    /// Hold controller1 and controller2 from frame on, until the next change
+    pub fn press(&mut self, frame: u64, controller1: Buttons, controller2: Buttons) {
+        let change = (frame, [controller1, controller2]);
+        match self.changes.binary_search_by_key(&frame, |&(frame, _)| frame) {
+            Ok(index) => self.changes[index] = change,
+            Err(index) => self.changes.insert(index, change),
+        }
+    }
+
+    /// Read a script with a change a line: the frame, then controller 1's
+    /// buttons and optionally controller 2's, which are none when left
+    /// out. Buttons are written as Buttons::parse() takes them, and
+    /// anything after a # is a comment.
+    ///
+    /// ```text
+    /// 60  start
+    /// 62  -
+    /// 90  a+right  b
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, InputError> {
+        let mut script = InputScript::new();
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: String| InputError { line: index + 1, message };
+            let line = line.split('#').next().unwrap();
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.is_empty() {
+                continue;
+            }
+            if fields.len() > 3 {
+                return Err(error("expected a frame and one or two controllers".to_string()));
+            }
+            let frame = fields[0]
+                .parse()
+                .map_err(|_| error(format!("{} is not a frame number", fields[0])))?;
+            let mut controllers = [Buttons::NONE; 2];
+            for (buttons, field) in controllers.iter_mut().zip(&fields[1..]) {
+                *buttons = Buttons::parse(field).ok_or_else(|| error(format!("unknown buttons {}", field)))?;
+            }
+            script.press(frame, controllers[0], controllers[1]);
+        }
+        Ok(script)
+    }
+
+    /// What the controllers hold at frame, None before the first change
+    pub fn at(&self, frame: u64) -> Option<[Buttons; 2]> {
+        self.latest(0..=frame)
+    }
+
+    // the last change made in frames
+    fn latest(&self, frames: RangeInclusive<u64>) -> Option<[Buttons; 2]> {
+        self.changes
+            .iter()
+            .rev()
+            .find(|(frame, _)| frames.contains(frame))
+            .map(|&(_, controllers)| controllers)
+    }
+}
+
+/// A MemoryMap with the mapache64 vblank and controller registers in front
+/// of it. Call syncVblank() after every step() or tick() to keep them in
+/// time with the CPU's cycle count, or let runFrame() do it.
+pub struct Mapache64Bus {
+    pub map: MemoryMap,
+    cyclesPerFrame: u64,
+    // a frame is drawn for its first activeCycles, then it is in vblank
+    activeCycles: u64,
+    inVblank: bool,
+    irq: bool,
+    // vblanks begun and frames finished, counted from cycle 0
+    vblanks: u64,
+    frames: u64,
+    controllers: [Buttons; 2],
+    script: InputScript,
+}
+
+impl Mapache64Bus {
+    /// The board on map, usually MemoryMap::mapache64(), with the CPU
+    /// running at clockHz. Like 640x480 VGA, 45 of every 525 lines are
+    /// vblank.
+    pub fn new(map: MemoryMap, clockHz: u64) -> Self {
+        let cyclesPerFrame = (clockHz / FRAMES_PER_SECOND).max(1);
+        Mapache64Bus {
+            map,
+            cyclesPerFrame,
+            activeCycles: cyclesPerFrame * 480 / 525,
+            inVblank: false,
+            irq: false,
+            vblanks: 0,
+            frames: 0,
+            controllers: [Buttons::NONE; 2],
+            script: InputScript::new(),
+        }
+    }
+
+    pub fn cyclesPerFrame(&self) -> u64 {
+        self.cyclesPerFrame
+    }
+
+    pub fn inVblank(&self) -> bool {
+        self.inVblank
+    }
+
+    /// whether the vblank IRQ is waiting to be acknowledged
+    pub fn irqPending(&self) -> bool {
+        self.irq
+    }
+
+    /// frames finished since the cycle count started
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// What a controller holds
+    pub fn controller(&self, controller: Controller) -> Buttons {
+        self.controllers[controller as usize]
+    }
+
+    /// Hold buttons on a controller until they are set again
+    pub fn setController(&mut self, controller: Controller, buttons: Buttons) {
+        self.controllers[controller as usize] = buttons;
+    }
+
+    /// Press the buttons in script as frames finish, starting with what it
+    /// holds at the current frame
+    pub fn setInputScript(&mut self, script: InputScript) {
+        if let Some(controllers) = script.at(self.frames) {
+            self.controllers = controllers;
+        }
+        self.script = script;
+    }
+}
+
+impl Bus for Mapache64Bus {
+    fn read(&mut self, addr: u16) -> u8 {
+        match addr {
+            IN_VBLANK => self.inVblank as u8,
+            CONTROLLER_1 => self.controllers[0].bits(),
+            CONTROLLER_2 => self.controllers[1].bits(),
+            _ => self.map.read(addr),
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            CLR_VBLANK_IRQ => self.irq = false,
+            CONTROLLER_1 | CONTROLLER_2 => (),
+            _ => self.map.write(addr, value),
+        }
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        match addr {
+            IN_VBLANK => self.inVblank as u8,
+            CONTROLLER_1 => self.controllers[0].bits(),
+            CONTROLLER_2 => self.controllers[1].bits(),
+            _ => self.map.peek(addr),
+        }
+    }
+
+    fn takeFault(&mut self) -> Option<BusFault> {
+        self.map.takeFault()
+    }
+
+    fn saveStateKind(&self) -> &'static str {
+        "Mapache64Bus"
+    }
+
+    // the map's state with its length in front, then the vblank state and
+    // the buttons held. The clock and input script are host setup and
+    // aren't saved.
+    fn saveState(&self, out: &mut StateWriter) {
+        let mut map = StateWriter::new();
+        self.map.saveState(&mut map);
+        let map = map.finish();
+        out.u32(map.len() as u32);
+        out.bytes(&map);
+        out.bool(self.inVblank);
+        out.bool(self.irq);
+        out.u64(self.vblanks);
+        out.u64(self.frames);
+        out.u8(self.controllers[0].bits());
+        out.u8(self.controllers[1].bits());
+    }
+
+    fn loadState(&mut self, input: &mut StateReader) -> Result<(), SaveStateError> {
+        let length = input.u32()? as usize;
+        let map = input.bytes(length)?;
+        let inVblank = input.bool()?;
+        let irq = input.bool()?;
+        let vblanks = input.u64()?;
+        let frames = input.u64()?;
+        let controller1 = input.u8()?;
+        let controller2 = input.u8()?;
+        if input.remaining() != 0 {
+            return Err(SaveStateError::new("not a Mapache64Bus state"));
+        }
+        self.map.loadState(&mut StateReader::new(map))?;
+        self.inVblank = inVblank;
+        self.irq = irq;
+        self.vblanks = vblanks;
+        self.frames = frames;
+        self.controllers = [Buttons::fromBits(controller1), Buttons::fromBits(controller2)];
+        Ok(())
+    }
+}
+
+/// Bring the vblank registers and the IRQ line up to the CPU's cycle
+/// count. IRQ is raised as vblank begins and held until the game writes
+/// CLR_VBLANK_IRQ. Returns true when a frame finished since the last call,
+/// which is when the GPU starts showing what fill_vram left in VRAM and
+/// when the input script's next buttons are pressed.
+pub fn syncVblank(cpu: &mut Mpu6502<Mapache64Bus>) -> bool {
+    let cycles = cpu.processorCycles();
+    let bus = cpu.busMut();
+    let frame = cycles / bus.cyclesPerFrame;
+    let inVblank = cycles % bus.cyclesPerFrame >= bus.activeCycles;
+    let vblanks = frame + inVblank as u64;
+    // the count only goes back when reset() restarts the cycle count
+    if vblanks > bus.vblanks {
+        bus.irq = true;
+    }
+    bus.vblanks = vblanks;
+    bus.inVblank = inVblank;
+    let finished = frame > bus.frames;
+    if finished {
+        if let Some(controllers) = bus.script.latest(bus.frames + 1..=frame) {
+            bus.controllers = controllers;
+        }
+    }
+    bus.frames = frame;
+    let irq = bus.irq;
+    cpu.setIrq(irq);
+    finished
+}
+
+/// Step until the current frame finishes
+pub fn runFrame(cpu: &mut Mpu6502<Mapache64Bus>) -> Result<(), StepError> {
+    loop {
+        cpu.step()?;
+        if syncVblank(cpu) {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::CpuVariant;
+    use crate::cpu::RunState;
+
+    #[test]
+    fn controllers() {
+        // LDA CONTROLLER_1, STA $10, LDA CONTROLLER_2, STA $11, STP
+        let mut map = MemoryMap::mapache64();
+        map.load(0x8000, &[0xad, 0x02, 0x70, 0x85, 0x10, 0xad, 0x03, 0x70, 0x85, 0x11, 0xdb]);
+        map.load(0xfffc, &[0x00, 0x80]);
+        let mut cpu = Mpu6502::withBus(CpuVariant::Wdc65C02, Mapache64Bus::new(map, DEFAULT_CLOCK_HZ));
+        cpu.busMut().setController(Controller::One, Buttons::A | Buttons::LEFT);
+        cpu.busMut().setController(Controller::Two, Buttons::START);
+        assert_eq!(cpu.bus().controller(Controller::One), Buttons::A | Buttons::LEFT);
+        assert_eq!(cpu.busMut().read(CONTROLLER_1), 0x82);
+        assert_eq!(cpu.busMut().read(CONTROLLER_2), 0x10);
+
+        cpu.reset();
+        while cpu.runState() != RunState::Stopped {
+            cpu.step().unwrap();
+        }
+        assert_eq!(cpu.bus().map.memory[0x10..0x12], [0x82, 0x10]);
+    }
+
+    // code at 0x8000 and the IRQ handler at 0x8010, a frame every 1000
+    // cycles with vblank from cycle 914
+    fn board(code: &[u8], handler: &[u8]) -> Mpu6502<Mapache64Bus> {
+        let mut map = MemoryMap::mapache64();
+        map.load(0x8000, code);
+        map.load(0x8010, handler);
+        map.load(0xfffc, &[0x00, 0x80, 0x10, 0x80]);
+        let mut cpu = Mpu6502::withBus(CpuVariant::Wdc65C02, Mapache64Bus::new(map, 60_000));
+        cpu.reset();
+        cpu
+    }
+
+    #[test]
+    fn vblankIrq() {
+        // SEI, then JMP to itself, so the IRQ is left to the host
+        let mut cpu = board(&[0x78, 0x4c, 0x01, 0x80], &[]);
+        assert_eq!(cpu.bus().cyclesPerFrame(), 1000);
+        let mut irqs = 0;
+        while cpu.processorCycles() < 3500 {
+            cpu.step().unwrap();
+            syncVblank(&mut cpu);
+            let inVblank = cpu.processorCycles() % 1000 >= 914;
+            assert_eq!(cpu.bus().inVblank(), inVblank);
+            assert_eq!(cpu.busMut().read(IN_VBLANK), inVblank as u8);
+            if cpu.bus().irqPending() {
+                irqs += 1;
+                assert!(inVblank);
+                cpu.busMut().write(CLR_VBLANK_IRQ, 0);
+                assert!(!cpu.bus().irqPending());
+            }
+        }
+        assert_eq!((irqs, cpu.bus().frames()), (3, 3));
+    }
+
+    #[test]
+    fn vblankIrqHandler() {
+        // CLI, then INC $10 in a loop. The handler acknowledges the IRQ with
+        // STA CLR_VBLANK_IRQ and counts it in $12.
+        let mut cpu = board(&[0x58, 0xe6, 0x10, 0x4c, 0x01, 0x80], &[0x8d, 0x01, 0x70, 0xe6, 0x12, 0x40]);
+        for _ in 0..5 {
+            runFrame(&mut cpu).unwrap();
+        }
+        assert_eq!(cpu.bus().frames(), 5);
+        assert_eq!(cpu.bus().map.memory[0x12], 5);
+        assert!(!cpu.bus().irqPending());
+    }
+
+    #[test]
+    fn inputScript() {
+        let script = InputScript::parse("# frame  1  2\n2 a+Start b\n\n0 right # held first\n4 -\n").unwrap();
+        assert_eq!(script.at(0), Some([Buttons::RIGHT, Buttons::NONE]));
+        assert_eq!(script.at(3), Some([Buttons::A | Buttons::START, Buttons::B]));
+        assert_eq!(script.at(9), Some([Buttons::NONE, Buttons::NONE]));
+    }
+
+    #[test]
+    fn inputScriptErrors() {
+        let error = InputScript::parse("0 a\n1 a+jump\n").unwrap_err();
+        assert_eq!((error.line, error.message.as_str()), (2, "unknown buttons a+jump"));
+        let error = InputScript::parse("soon a\n").unwrap_err();
+        assert_eq!((error.line, error.message.as_str()), (1, "soon is not a frame number"));
+    }
+}
@@ -0,0 +1,132 @@
+//! The save state format. A save state starts with SAVE_STATE_MAGIC, a
+//! little endian u16 version and the Bus::saveStateKind() of the bus it
+//! was saved from with a u8 length in front. Then come the CPU's registers,
+//! cycle count, interrupt lines and tick() progress, then the bus state
+//! with its length in front. Numbers are little endian throughout.
+
+use std::fmt;
+
+/// The first bytes of every save state
+pub const SAVE_STATE_MAGIC: &[u8; 8] = b"6502SAVE";
+/// Bumped whenever the layout changes, older states are refused
+pub const SAVE_STATE_VERSION: u16 = 2;
+
+/// Why a save state couldn't be restored
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SaveStateError {
+    pub message: String,
+}
+
+impl SaveStateError {
+    pub fn new(message: impl Into<String>) -> Self {
+        SaveStateError {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SaveStateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "save state: {}", self.message)
+    }
+}
+
+impl std::error::Error for SaveStateError {}
+
+/// Builds a save state. Buses write their state through one in
+/// Bus::saveState.
+pub struct StateWriter {
+    bytes: Vec<u8>,
+}
+
+impl StateWriter {
+    pub fn new() -> Self {
+        StateWriter { bytes: Vec::new() }
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.bytes.push(value as u8);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// bytes as they are, the reader has to know how many there are
+    pub fn bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl Default for StateWriter {
+    fn default() -> Self {
+        StateWriter::new()
+    }
+}
+
+/// Reads a save state back in the order a StateWriter wrote it. Running
+/// off the end is an error rather than a panic.
+pub struct StateReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> StateReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        StateReader { bytes }
+    }
+
+    /// the next count bytes
+    pub fn bytes(&mut self, count: usize) -> Result<&'a [u8], SaveStateError> {
+        if count > self.bytes.len() {
+            return Err(SaveStateError::new("ends too soon"));
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, SaveStateError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool, SaveStateError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(SaveStateError::new(format!("{} is not a bool", value))),
+        }
+    }
+
+    pub fn u16(&mut self) -> Result<u16, SaveStateError> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, SaveStateError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, SaveStateError> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    /// how many bytes haven't been read yet
+    pub fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+}
@@ -0,0 +1,259 @@
+//! A headless renderer for the mapache64 GPU. It draws what VRAM at
+//! 0x4000-0x4fff holds the way the board would put it on screen, so a run
+//! can be checked without the web VRAM dump viewer, and writes the frame
+//! as PNG or PPM without any outside crates.
+//! See: <https://mapache64.ucsbieee.org/guides/gpu/>
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::bus::Bus;
+use crate::vram::Vram;
+
+mod font;
+
+use font::FONT;
+
+pub const SCREEN_WIDTH: usize = 256;
+pub const SCREEN_HEIGHT: usize = 240;
+
+/// A 256x240 picture, one RGB triple per pixel, row by row from the top
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Frame {
+    /// all black
+    pub fn new() -> Self {
+        Frame {
+            pixels: vec![[0; 3]; SCREEN_WIDTH * SCREEN_HEIGHT],
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        self.pixels[y * SCREEN_WIDTH + x]
+    }
+
+    pub fn pixels(&self) -> &[[u8; 3]] {
+        &self.pixels
+    }
+
+    fn set(&mut self, x: usize, y: usize, rgb: [u8; 3]) {
+        self.pixels[y * SCREEN_WIDTH + x] = rgb;
+    }
+
+    /// A binary PPM (P6)
+    pub fn toPpm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", SCREEN_WIDTH, SCREEN_HEIGHT).into_bytes();
+        out.extend(self.pixels.iter().flatten());
+        out
+    }
+
+    /// An 8 bit RGB PNG. The image data is stored uncompressed in the zlib
+    /// stream, which every decoder reads, so no deflate encoder is needed.
+    pub fn toPng(&self) -> Vec<u8> {
+        // each row starts with filter type 0, none
+        let mut raw = Vec::with_capacity((SCREEN_WIDTH * 3 + 1) * SCREEN_HEIGHT);
+        for row in self.pixels.chunks(SCREEN_WIDTH) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+
+        let mut zlib = vec![0x78, 0x01];
+        let mut blocks = raw.chunks(0xffff).peekable();
+        while let Some(block) = blocks.next() {
+            zlib.push(blocks.peek().is_none() as u8);
+            zlib.extend_from_slice(&(block.len() as u16).to_le_bytes());
+            zlib.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+            zlib.extend_from_slice(block);
+        }
+        zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&(SCREEN_WIDTH as u32).to_be_bytes());
+        header.extend_from_slice(&(SCREEN_HEIGHT as u32).to_be_bytes());
+        // 8 bits per channel, RGB, deflate, adaptive filters, no interlace
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        let mut out = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        pngChunk(&mut out, b"IHDR", &header);
+        pngChunk(&mut out, b"IDAT", &zlib);
+        pngChunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    /// Write as PPM when path ends in .ppm and as PNG otherwise
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let ppm = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("ppm"));
+        fs::write(path, if ppm { self.toPpm() } else { self.toPng() })
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Frame::new()
+    }
+}
+
+fn pngChunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+// The CRC PNG chunks end with, the one from zlib and Ethernet
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xedb88320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(0xffffffff, |crc, &b| {
+        CRC_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+// A 3 bit color (red 4, green 2, blue 1) at one of the 4 shades a 2 bit
+// pattern pixel picks, 0 being black
+fn shade(color: u8, value: u8) -> [u8; 3] {
+    let level = value * 0x55;
+    [
+        if color & 0x4 != 0 { level } else { 0 },
+        if color & 0x2 != 0 { level } else { 0 },
+        if color & 0x1 != 0 { level } else { 0 },
+    ]
+}
+
+/// Draw VRAM. The layers go from the back:
+/// - the NTBL background, 30 rows of 32 PMB tiles, each in one of the two
+///   colors of background_palette (colorSelect picks the second)
+/// - the 64 OBM sprites from PMF, where pattern value 0 is see-through and
+///   the lower numbered sprite is in front
+/// - the TXBL text, whose characters are drawn in the background palette
+///   color colorSelect picks, leaving the layers below showing around them
+pub fn renderVram(vram: &Vram) -> Frame {
+    let mut frame = Frame::new();
+    let palette = vram.backgroundPalette();
+
+    for row in 0..SCREEN_HEIGHT / 8 {
+        for column in 0..SCREEN_WIDTH / 8 {
+            let tile = vram.tile(row, column);
+            let pattern = vram.backgroundPattern(tile.pattern as usize);
+            let color = palette.color(tile.colorSelect);
+            for y in 0..8 {
+                for x in 0..8 {
+                    let value = pattern.flippedPixel(tile.hflip, tile.vflip, x, y);
+                    frame.set(column * 8 + x, row * 8 + y, shade(color, value));
+                }
+            }
+        }
+    }
+
+    let objects: Vec<_> = vram.objects().collect();
+    for object in objects.iter().rev() {
+        let pattern = vram.foregroundPattern(object.pattern as usize);
+        for y in 0..8 {
+            for x in 0..8 {
+                let (screenX, screenY) = (object.x as usize + x, object.y as usize + y);
+                let value = pattern.flippedPixel(object.hflip, object.vflip, x, y);
+                // sprites past the right or bottom edge are cut off
+                if value != 0 && screenX < SCREEN_WIDTH && screenY < SCREEN_HEIGHT {
+                    frame.set(screenX, screenY, shade(object.color, value));
+                }
+            }
+        }
+    }
+
+    for row in 0..SCREEN_HEIGHT / 8 {
+        for column in 0..SCREEN_WIDTH / 8 {
+            let character = vram.text(row, column);
+            let color = palette.color(character.colorSelect);
+            for (y, bits) in FONT[character.value as usize].iter().enumerate() {
+                for x in 0..8 {
+                    if bits & (1 << x) != 0 {
+                        frame.set(column * 8 + x, row * 8 + y, shade(color, 3));
+                    }
+                }
+            }
+        }
+    }
+    frame
+}
+
+/// Draw the VRAM on bus
+pub fn renderScreen<B: Bus>(bus: &B) -> Frame {
+    renderVram(&Vram::read(bus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vram::BACKGROUND_PALETTE;
+    use crate::vram::NTBL_START;
+    use crate::vram::OBM_START;
+    use crate::vram::PMB_START;
+    use crate::vram::PMF_START;
+    use crate::vram::VRAM_SIZE;
+    use crate::vram::VRAM_START;
+
+    fn vramWith(writes: &[(u16, &[u8])]) -> Vram {
+        let mut bytes = [0; VRAM_SIZE];
+        for &(addr, data) in writes {
+            let offset = (addr - VRAM_START) as usize;
+            bytes[offset..offset + data.len()].copy_from_slice(data);
+        }
+        Vram::fromBytes(bytes)
+    }
+
+    #[test]
+    fn renderedPixels() {
+        let frame = renderVram(&vramWith(&[
+            // green and magenta
+            (BACKGROUND_PALETTE, &[0x2a]),
+            // PMB[1] starts with pixels 3, 2, 1, 0
+            (PMB_START + 16, &[0xe4]),
+            // PMF[2] starts with pixels 3, 0
+            (PMF_START + 32, &[0xc0]),
+            // NTBL[0][0] is PMB[1] in the second color, NTBL[0][1] the same
+            // in the first color and flipped
+            (NTBL_START, &[0x81, 0x41]),
+            // a red PMF[2] at 16, 16
+            (OBM_START, &[16, 16, 0x02, 0x04]),
+        ]));
+        assert_eq!(frame.pixel(0, 0), [0xff, 0x00, 0xff]);
+        assert_eq!(frame.pixel(1, 0), [0xaa, 0x00, 0xaa]);
+        assert_eq!(frame.pixel(2, 0), [0x55, 0x00, 0x55]);
+        assert_eq!(frame.pixel(3, 0), [0x00, 0x00, 0x00]);
+        assert_eq!(frame.pixel(15, 0), [0x00, 0xff, 0x00]);
+        assert_eq!(frame.pixel(8, 0), [0x00, 0x00, 0x00]);
+        // the sprite's pixel 0 lets the background through
+        assert_eq!(frame.pixel(16, 16), [0xff, 0x00, 0x00]);
+        assert_eq!(frame.pixel(17, 16), [0x00, 0x00, 0x00]);
+    }
+}
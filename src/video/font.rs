@@ -0,0 +1,102 @@
+// The TXBL character set. The real one lives in the FPGA and isn't in the
+// firmware sources, so this is the public domain font8x8_basic by Daniel
+// Hepper for printable ASCII, 0x20-0x7e. Each glyph is 8 rows from the top
+// with the leftmost pixel in bit 0. The control characters are blank.
+pub(super) const FONT: [[u8; 8]; 128] = {
+    let mut font = [[0; 8]; 128];
+    font[0x21] = [0x18, 0x3c, 0x3c, 0x18, 0x18, 0x00, 0x18, 0x00]; // !
+    font[0x22] = [0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]; // "
+    font[0x23] = [0x36, 0x36, 0x7f, 0x36, 0x7f, 0x36, 0x36, 0x00]; // #
+    font[0x24] = [0x0c, 0x3e, 0x03, 0x1e, 0x30, 0x1f, 0x0c, 0x00]; // $
+    font[0x25] = [0x00, 0x63, 0x33, 0x18, 0x0c, 0x66, 0x63, 0x00]; // %
+    font[0x26] = [0x1c, 0x36, 0x1c, 0x6e, 0x3b, 0x33, 0x6e, 0x00]; // &
+    font[0x27] = [0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00]; // '
+    font[0x28] = [0x18, 0x0c, 0x06, 0x06, 0x06, 0x0c, 0x18, 0x00]; // (
+    font[0x29] = [0x06, 0x0c, 0x18, 0x18, 0x18, 0x0c, 0x06, 0x00]; // )
+    font[0x2a] = [0x00, 0x66, 0x3c, 0xff, 0x3c, 0x66, 0x00, 0x00]; // *
+    font[0x2b] = [0x00, 0x0c, 0x0c, 0x3f, 0x0c, 0x0c, 0x00, 0x00]; // +
+    font[0x2c] = [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c, 0x06]; // ,
+    font[0x2d] = [0x00, 0x00, 0x00, 0x3f, 0x00, 0x00, 0x00, 0x00]; // -
+    font[0x2e] = [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c, 0x00]; // .
+    font[0x2f] = [0x60, 0x30, 0x18, 0x0c, 0x06, 0x03, 0x01, 0x00]; // /
+    font[0x30] = [0x3e, 0x63, 0x73, 0x7b, 0x6f, 0x67, 0x3e, 0x00]; // 0
+    font[0x31] = [0x0c, 0x0e, 0x0c, 0x0c, 0x0c, 0x0c, 0x3f, 0x00]; // 1
+    font[0x32] = [0x1e, 0x33, 0x30, 0x1c, 0x06, 0x33, 0x3f, 0x00]; // 2
+    font[0x33] = [0x1e, 0x33, 0x30, 0x1c, 0x30, 0x33, 0x1e, 0x00]; // 3
+    font[0x34] = [0x38, 0x3c, 0x36, 0x33, 0x7f, 0x30, 0x78, 0x00]; // 4
+    font[0x35] = [0x3f, 0x03, 0x1f, 0x30, 0x30, 0x33, 0x1e, 0x00]; // 5
+    font[0x36] = [0x1c, 0x06, 0x03, 0x1f, 0x33, 0x33, 0x1e, 0x00]; // 6
+    font[0x37] = [0x3f, 0x33, 0x30, 0x18, 0x0c, 0x0c, 0x0c, 0x00]; // 7
+    font[0x38] = [0x1e, 0x33, 0x33, 0x1e, 0x33, 0x33, 0x1e, 0x00]; // 8
+    font[0x39] = [0x1e, 0x33, 0x33, 0x3e, 0x30, 0x18, 0x0e, 0x00]; // 9
+    font[0x3a] = [0x00, 0x0c, 0x0c, 0x00, 0x00, 0x0c, 0x0c, 0x00]; // :
+    font[0x3b] = [0x00, 0x0c, 0x0c, 0x00, 0x00, 0x0c, 0x0c, 0x06]; // ;
+    font[0x3c] = [0x18, 0x0c, 0x06, 0x03, 0x06, 0x0c, 0x18, 0x00]; // <
+    font[0x3d] = [0x00, 0x00, 0x3f, 0x00, 0x00, 0x3f, 0x00, 0x00]; // =
+    font[0x3e] = [0x06, 0x0c, 0x18, 0x30, 0x18, 0x0c, 0x06, 0x00]; // >
+    font[0x3f] = [0x1e, 0x33, 0x30, 0x18, 0x0c, 0x00, 0x0c, 0x00]; // ?
+    font[0x40] = [0x3e, 0x63, 0x7b, 0x7b, 0x7b, 0x03, 0x1e, 0x00]; // @
+    font[0x41] = [0x0c, 0x1e, 0x33, 0x33, 0x3f, 0x33, 0x33, 0x00]; // A
+    font[0x42] = [0x3f, 0x66, 0x66, 0x3e, 0x66, 0x66, 0x3f, 0x00]; // B
+    font[0x43] = [0x3c, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3c, 0x00]; // C
+    font[0x44] = [0x1f, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1f, 0x00]; // D
+    font[0x45] = [0x7f, 0x46, 0x16, 0x1e, 0x16, 0x46, 0x7f, 0x00]; // E
+    font[0x46] = [0x7f, 0x46, 0x16, 0x1e, 0x16, 0x06, 0x0f, 0x00]; // F
+    font[0x47] = [0x3c, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7c, 0x00]; // G
+    font[0x48] = [0x33, 0x33, 0x33, 0x3f, 0x33, 0x33, 0x33, 0x00]; // H
+    font[0x49] = [0x1e, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x1e, 0x00]; // I
+    font[0x4a] = [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1e, 0x00]; // J
+    font[0x4b] = [0x67, 0x66, 0x36, 0x1e, 0x36, 0x66, 0x67, 0x00]; // K
+    font[0x4c] = [0x0f, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7f, 0x00]; // L
+    font[0x4d] = [0x63, 0x77, 0x7f, 0x7f, 0x6b, 0x63, 0x63, 0x00]; // M
+    font[0x4e] = [0x63, 0x67, 0x6f, 0x7b, 0x73, 0x63, 0x63, 0x00]; // N
+    font[0x4f] = [0x1c, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1c, 0x00]; // O
+    font[0x50] = [0x3f, 0x66, 0x66, 0x3e, 0x06, 0x06, 0x0f, 0x00]; // P
+    font[0x51] = [0x1e, 0x33, 0x33, 0x33, 0x3b, 0x1e, 0x38, 0x00]; // Q
+    font[0x52] = [0x3f, 0x66, 0x66, 0x3e, 0x36, 0x66, 0x67, 0x00]; // R
+    font[0x53] = [0x1e, 0x33, 0x07, 0x0e, 0x38, 0x33, 0x1e, 0x00]; // S
+    font[0x54] = [0x3f, 0x2d, 0x0c, 0x0c, 0x0c, 0x0c, 0x1e, 0x00]; // T
+    font[0x55] = [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3f, 0x00]; // U
+    font[0x56] = [0x33, 0x33, 0x33, 0x33, 0x33, 0x1e, 0x0c, 0x00]; // V
+    font[0x57] = [0x63, 0x63, 0x63, 0x6b, 0x7f, 0x77, 0x63, 0x00]; // W
+    font[0x58] = [0x63, 0x63, 0x36, 0x1c, 0x1c, 0x36, 0x63, 0x00]; // X
+    font[0x59] = [0x33, 0x33, 0x33, 0x1e, 0x0c, 0x0c, 0x1e, 0x00]; // Y
+    font[0x5a] = [0x7f, 0x63, 0x31, 0x18, 0x4c, 0x66, 0x7f, 0x00]; // Z
+    font[0x5b] = [0x1e, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1e, 0x00]; // [
+    font[0x5c] = [0x03, 0x06, 0x0c, 0x18, 0x30, 0x60, 0x40, 0x00]; // backslash
+    font[0x5d] = [0x1e, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1e, 0x00]; // ]
+    font[0x5e] = [0x08, 0x1c, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00]; // ^
+    font[0x5f] = [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff]; // _
+    font[0x60] = [0x0c, 0x0c, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00]; // `
+    font[0x61] = [0x00, 0x00, 0x1e, 0x30, 0x3e, 0x33, 0x6e, 0x00]; // a
+    font[0x62] = [0x07, 0x06, 0x06, 0x3e, 0x66, 0x66, 0x3b, 0x00]; // b
+    font[0x63] = [0x00, 0x00, 0x1e, 0x33, 0x03, 0x33, 0x1e, 0x00]; // c
+    font[0x64] = [0x38, 0x30, 0x30, 0x3e, 0x33, 0x33, 0x6e, 0x00]; // d
+    font[0x65] = [0x00, 0x00, 0x1e, 0x33, 0x3f, 0x03, 0x1e, 0x00]; // e
+    font[0x66] = [0x1c, 0x36, 0x06, 0x0f, 0x06, 0x06, 0x0f, 0x00]; // f
+    font[0x67] = [0x00, 0x00, 0x6e, 0x33, 0x33, 0x3e, 0x30, 0x1f]; // g
+    font[0x68] = [0x07, 0x06, 0x36, 0x6e, 0x66, 0x66, 0x67, 0x00]; // h
+    font[0x69] = [0x0c, 0x00, 0x0e, 0x0c, 0x0c, 0x0c, 0x1e, 0x00]; // i
+    font[0x6a] = [0x30, 0x00, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1e]; // j
+    font[0x6b] = [0x07, 0x06, 0x66, 0x36, 0x1e, 0x36, 0x67, 0x00]; // k
+    font[0x6c] = [0x0e, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x1e, 0x00]; // l
+    font[0x6d] = [0x00, 0x00, 0x33, 0x7f, 0x7f, 0x6b, 0x63, 0x00]; // m
+    font[0x6e] = [0x00, 0x00, 0x1f, 0x33, 0x33, 0x33, 0x33, 0x00]; // n
+    font[0x6f] = [0x00, 0x00, 0x1e, 0x33, 0x33, 0x33, 0x1e, 0x00]; // o
+    font[0x70] = [0x00, 0x00, 0x3b, 0x66, 0x66, 0x3e, 0x06, 0x0f]; // p
+    font[0x71] = [0x00, 0x00, 0x6e, 0x33, 0x33, 0x3e, 0x30, 0x78]; // q
+    font[0x72] = [0x00, 0x00, 0x3b, 0x6e, 0x66, 0x06, 0x0f, 0x00]; // r
+    font[0x73] = [0x00, 0x00, 0x3e, 0x03, 0x1e, 0x30, 0x1f, 0x00]; // s
+    font[0x74] = [0x08, 0x0c, 0x3e, 0x0c, 0x0c, 0x2c, 0x18, 0x00]; // t
+    font[0x75] = [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x6e, 0x00]; // u
+    font[0x76] = [0x00, 0x00, 0x33, 0x33, 0x33, 0x1e, 0x0c, 0x00]; // v
+    font[0x77] = [0x00, 0x00, 0x63, 0x6b, 0x7f, 0x7f, 0x36, 0x00]; // w
+    font[0x78] = [0x00, 0x00, 0x63, 0x36, 0x1c, 0x36, 0x63, 0x00]; // x
+    font[0x79] = [0x00, 0x00, 0x33, 0x33, 0x33, 0x3e, 0x30, 0x1f]; // y
+    font[0x7a] = [0x00, 0x00, 0x3f, 0x19, 0x0c, 0x26, 0x3f, 0x00]; // z
+    font[0x7b] = [0x38, 0x0c, 0x0c, 0x07, 0x0c, 0x0c, 0x38, 0x00]; // {
+    font[0x7c] = [0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00]; // |
+    font[0x7d] = [0x07, 0x0c, 0x0c, 0x38, 0x0c, 0x0c, 0x07, 0x00]; // }
+    font[0x7e] = [0x6e, 0x3b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]; // ~
+    font
+};
@@ -0,0 +1,326 @@
+//! Typed views of mapache64 VRAM, following firmware/headers/vram.h, so
+//! tests can ask what a game put on screen without decoding bytes by hand.
+//!
+//! ```no_run
+//! # use emulator_6502::{CpuVariant, MemoryMap, Mpu6502, Vram};
+//! # let cpu = Mpu6502::withBus(CpuVariant::Wdc65C02, MemoryMap::mapache64());
+//! let vram = Vram::read(cpu.bus());
+//! let sprite = vram.object(3);
+//! assert_eq!((sprite.x, sprite.y, sprite.pattern), (40, 100, 5));
+//! ```
+
+use crate::bus::Bus;
+
+/// Where VRAM is on the bus and how big it is
+pub const VRAM_START: u16 = 0x4000;
+pub const VRAM_SIZE: usize = 0x1000;
+
+/// The sections of VRAM, as vram.c and mapache64.cfg place them
+pub const PMF_START: u16 = 0x4000;
+pub const PMB_START: u16 = 0x4200;
+pub const NTBL_START: u16 = 0x4400;
+pub const BACKGROUND_PALETTE: u16 = 0x47c0;
+pub const OBM_START: u16 = 0x4800;
+pub const TXBL_START: u16 = 0x4900;
+
+/// Patterns in each of PMF and PMB, sprites in OBM
+pub const PATTERN_COUNT: usize = 32;
+pub const OBJECT_COUNT: usize = 64;
+/// NTBL and TXBL are 30 rows of 32 tiles
+pub const TABLE_ROWS: usize = 30;
+pub const TABLE_COLUMNS: usize = 32;
+
+/// The tile, sprite and character bits from vram.h
+pub const COLOR_SELECT_MASK: u8 = 0x80;
+pub const HFLIP_MASK: u8 = 0x40;
+pub const VFLIP_MASK: u8 = 0x20;
+pub const PATTERN_ADDRESS_MASK: u8 = 0x1f;
+pub const CHAR_VALUE_MASK: u8 = 0x7f;
+
+/// An 8x8 pattern, pattern_t: 2 bits a pixel, 4 pixels a byte with the
+/// leftmost in the top bits, 2 bytes a row, like
+/// backend/pattern_converter.py writes it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pattern(pub [u8; 16]);
+
+impl Pattern {
+    /// The 0-3 value of pixel x, y, 0 being the darkest
+    pub fn pixel(&self, x: usize, y: usize) -> u8 {
+        (self.0[y * 2 + x / 4] >> (6 - 2 * (x % 4))) & 0x3
+    }
+
+    /// pixel() as a tile or sprite with hflip and vflip shows it
+    pub fn flippedPixel(&self, hflip: bool, vflip: bool, x: usize, y: usize) -> u8 {
+        let x = if hflip { 7 - x } else { x };
+        let y = if vflip { 7 - y } else { y };
+        self.pixel(x, y)
+    }
+}
+
+/// An NTBL entry, tile_t
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tile {
+    /// use the second background_palette color
+    pub colorSelect: bool,
+    pub hflip: bool,
+    pub vflip: bool,
+    /// index into PMB
+    pub pattern: u8,
+}
+
+impl Tile {
+    pub fn fromByte(byte: u8) -> Self {
+        Tile {
+            colorSelect: byte & COLOR_SELECT_MASK != 0,
+            hflip: byte & HFLIP_MASK != 0,
+            vflip: byte & VFLIP_MASK != 0,
+            pattern: byte & PATTERN_ADDRESS_MASK,
+        }
+    }
+}
+
+/// background_palette_t, the two colors background tiles and text use
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BackgroundPalette {
+    pub color0: u8,
+    pub color1: u8,
+}
+
+impl BackgroundPalette {
+    pub fn fromByte(byte: u8) -> Self {
+        BackgroundPalette {
+            color0: byte & 0x7,
+            color1: (byte >> 3) & 0x7,
+        }
+    }
+
+    /// the color a tile or character with colorSelect uses
+    pub fn color(&self, colorSelect: bool) -> u8 {
+        if colorSelect {
+            self.color1
+        } else {
+            self.color0
+        }
+    }
+}
+
+/// An OBM sprite, object_t
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Object {
+    /// pixels from the left of the screen
+    pub x: u8,
+    /// pixels from the top of the screen, games hide a sprite with 0xff
+    pub y: u8,
+    pub hflip: bool,
+    pub vflip: bool,
+    /// index into PMF
+    pub pattern: u8,
+    /// 3 bit RGB, red 4, green 2, blue 1
+    pub color: u8,
+}
+
+impl Object {
+    pub fn fromBytes(bytes: [u8; 4]) -> Self {
+        let [x, y, config, color] = bytes;
+        Object {
+            x,
+            y,
+            hflip: config & HFLIP_MASK != 0,
+            vflip: config & VFLIP_MASK != 0,
+            pattern: config & PATTERN_ADDRESS_MASK,
+            color: color & 0x7,
+        }
+    }
+}
+
+/// A TXBL entry, char_tile_t
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CharTile {
+    /// use the second background_palette color
+    pub colorSelect: bool,
+    /// the 7 bit character
+    pub value: u8,
+}
+
+impl CharTile {
+    pub fn fromByte(byte: u8) -> Self {
+        CharTile {
+            colorSelect: byte & COLOR_SELECT_MASK != 0,
+            value: byte & CHAR_VALUE_MASK,
+        }
+    }
+
+    /// the character as ASCII
+    pub fn char(&self) -> char {
+        self.value as char
+    }
+
+    /// char() for printing: 0, what an untouched table holds, is a space
+    /// and the other control characters are dots
+    pub fn printable(&self) -> char {
+        match self.value {
+            0 => ' ',
+            0x20..=0x7e => self.value as char,
+            _ => '.',
+        }
+    }
+}
+
+/// A copy of the 4K of VRAM with accessors for each table. Indexes past
+/// the end of a table panic like the C arrays would overrun.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vram {
+    bytes: [u8; VRAM_SIZE],
+}
+
+impl Vram {
+    pub fn fromBytes(bytes: [u8; VRAM_SIZE]) -> Self {
+        Vram { bytes }
+    }
+
+    /// VRAM on bus
+    pub fn read<B: Bus>(bus: &B) -> Self {
+        let mut bytes = [0; VRAM_SIZE];
+        for (offset, byte) in bytes.iter_mut().enumerate() {
+            *byte = bus.peek(VRAM_START + offset as u16);
+        }
+        Vram { bytes }
+    }
+
+    pub fn bytes(&self) -> &[u8; VRAM_SIZE] {
+        &self.bytes
+    }
+
+    // the byte at a bus address in VRAM
+    fn at(&self, addr: u16) -> u8 {
+        self.bytes[(addr - VRAM_START) as usize]
+    }
+
+    fn pattern(&self, start: u16, index: usize) -> Pattern {
+        assert!(index < PATTERN_COUNT, "pattern {} is past the end", index);
+        let offset = (start - VRAM_START) as usize + index * 16;
+        Pattern(self.bytes[offset..offset + 16].try_into().unwrap())
+    }
+
+    /// `PMF[index]`, the sprite patterns
+    pub fn foregroundPattern(&self, index: usize) -> Pattern {
+        self.pattern(PMF_START, index)
+    }
+
+    /// `PMB[index]`, the background tile patterns
+    pub fn backgroundPattern(&self, index: usize) -> Pattern {
+        self.pattern(PMB_START, index)
+    }
+
+    fn tableEntry(&self, start: u16, row: usize, column: usize) -> u8 {
+        assert!(
+            row < TABLE_ROWS && column < TABLE_COLUMNS,
+            "tile {}, {} is off the table",
+            row,
+            column
+        );
+        self.at(start + (row * TABLE_COLUMNS + column) as u16)
+    }
+
+    /// `NTBL[row][column]`
+    pub fn tile(&self, row: usize, column: usize) -> Tile {
+        Tile::fromByte(self.tableEntry(NTBL_START, row, column))
+    }
+
+    pub fn backgroundPalette(&self) -> BackgroundPalette {
+        BackgroundPalette::fromByte(self.at(BACKGROUND_PALETTE))
+    }
+
+    /// `OBM[index]`
+    pub fn object(&self, index: usize) -> Object {
+        assert!(index < OBJECT_COUNT, "object {} is past the end", index);
+        let offset = (OBM_START - VRAM_START) as usize + index * 4;
+        Object::fromBytes(self.bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// All of OBM, in order
+    pub fn objects(&self) -> impl Iterator<Item = Object> + '_ {
+        (0..OBJECT_COUNT).map(|index| self.object(index))
+    }
+
+    /// `TXBL[row][column]`
+    pub fn text(&self, row: usize, column: usize) -> CharTile {
+        CharTile::fromByte(self.tableEntry(TXBL_START, row, column))
+    }
+
+    /// All of TXBL as 30 lines of 32 printable() characters, each ending in
+    /// a newline, for comparing against golden output
+    pub fn textTable(&self) -> String {
+        let mut text = String::with_capacity(TABLE_ROWS * (TABLE_COLUMNS + 1));
+        for row in 0..TABLE_ROWS {
+            for column in 0..TABLE_COLUMNS {
+                text.push(self.text(row, column).printable());
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vramWith(writes: &[(u16, &[u8])]) -> Vram {
+        let mut bytes = [0; VRAM_SIZE];
+        for &(addr, data) in writes {
+            let offset = (addr - VRAM_START) as usize;
+            bytes[offset..offset + data.len()].copy_from_slice(data);
+        }
+        Vram::fromBytes(bytes)
+    }
+
+    #[test]
+    fn tiles() {
+        let vram = vramWith(&[(NTBL_START + 2 * 32 + 3, &[0xe5, 0x1f]), (BACKGROUND_PALETTE, &[0x2a])]);
+        let tile = Tile { colorSelect: true, hflip: true, vflip: true, pattern: 5 };
+        assert_eq!(vram.tile(2, 3), tile);
+        let tile = Tile { colorSelect: false, hflip: false, vflip: false, pattern: 31 };
+        assert_eq!(vram.tile(2, 4), tile);
+        assert_eq!(vram.backgroundPalette(), BackgroundPalette { color0: 2, color1: 5 });
+    }
+
+    #[test]
+    fn patterns() {
+        // PMF[1] rows 0 and 7: pixels 3, 2, 1, 0, 0, 0, 0, 1
+        let vram = vramWith(&[(PMF_START + 16, &[0xe4, 0x01]), (PMF_START + 30, &[0xe4, 0x01])]);
+        let pattern = vram.foregroundPattern(1);
+        let row: Vec<_> = (0..8).map(|x| pattern.pixel(x, 0)).collect();
+        assert_eq!(row, [3, 2, 1, 0, 0, 0, 0, 1]);
+        assert_eq!(pattern.pixel(0, 1), 0);
+        assert_eq!(pattern.flippedPixel(true, false, 0, 0), 1);
+        assert_eq!(pattern.flippedPixel(true, false, 6, 0), 2);
+        assert_eq!(pattern.flippedPixel(false, true, 1, 0), 2);
+        assert_eq!(pattern.flippedPixel(false, true, 1, 1), 0);
+        assert_eq!(vram.backgroundPattern(1), Pattern([0; 16]));
+    }
+
+    #[test]
+    fn objects() {
+        let vram = vramWith(&[(OBM_START + 3 * 4, &[40, 100, 0x45, 0x0e, 8, 0xff, 0x3f, 0x01])]);
+        let object = Object { x: 40, y: 100, hflip: true, vflip: false, pattern: 5, color: 6 };
+        assert_eq!(vram.object(3), object);
+        let object = Object { x: 8, y: 0xff, hflip: false, vflip: true, pattern: 31, color: 1 };
+        assert_eq!(vram.object(4), object);
+        assert_eq!(vram.objects().filter(|object| object.x != 0).count(), 2);
+    }
+
+    #[test]
+    fn textTable() {
+        // the top bit is colorSelect, not part of the character
+        let vram = vramWith(&[(TXBL_START, &[0xc8, 0x69, 0x00, 0x81]), (TXBL_START + 29 * 32 + 31, &[0xa1])]);
+        assert_eq!(vram.text(0, 0), CharTile { colorSelect: true, value: b'H' });
+        assert_eq!(vram.text(0, 1), CharTile { colorSelect: false, value: b'i' });
+        assert_eq!(vram.text(29, 31).char(), '!');
+        let text = vram.textTable();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), TABLE_ROWS);
+        assert_eq!(lines[0], format!("Hi .{}", " ".repeat(28)));
+        assert_eq!(lines[29], format!("{}!", " ".repeat(31)));
+    }
+}